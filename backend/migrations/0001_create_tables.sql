@@ -1,4 +1,4 @@
-CREATE TABLE problems (
+CREATE TABLE IF NOT EXISTS problems (
     id             SERIAL PRIMARY KEY,
     chosen_date   DATE,
     position       INT,
@@ -8,7 +8,7 @@ CREATE TABLE problems (
     difficulty     INT
 );
 
-CREATE TABLE user_status (
+CREATE TABLE IF NOT EXISTS user_status (
     user_id          TEXT,
     problem_row_id   INT,
     accepted         BOOLEAN
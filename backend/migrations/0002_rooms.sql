@@ -0,0 +1,23 @@
+CREATE TYPE room_mode AS ENUM ('individual', 'team_shared', 'team_claim_first', 'lockout');
+
+CREATE TABLE rooms (
+    id           TEXT PRIMARY KEY,
+    name         TEXT,
+    mode         room_mode NOT NULL DEFAULT 'individual',
+    bands        JSONB,
+    start_time   TIMESTAMPTZ,
+    end_time     TIMESTAMPTZ
+);
+
+CREATE TABLE room_members (
+    room_id   TEXT REFERENCES rooms (id),
+    user_id   TEXT,
+    team      TEXT,
+    PRIMARY KEY (room_id, user_id)
+);
+
+ALTER TABLE problems ADD COLUMN room_id TEXT;
+
+ALTER TABLE user_status
+    ADD COLUMN accepted_time TIMESTAMPTZ,
+    ADD COLUMN submission_id BIGINT;
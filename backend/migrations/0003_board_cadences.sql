@@ -0,0 +1,7 @@
+CREATE TYPE board_cadence AS ENUM ('daily', 'weekly', 'monthly');
+
+ALTER TABLE problems
+    ADD COLUMN cadence board_cadence NOT NULL DEFAULT 'daily',
+    ADD COLUMN period_end DATE;
+
+UPDATE problems SET period_end = chosen_date;
@@ -0,0 +1,6 @@
+-- Keep one status for each pair, preferring an accepted one.
+DELETE FROM user_status a USING user_status b
+WHERE a.user_id = b.user_id AND a.problem_row_id = b.problem_row_id
+    AND (a.accepted, a.ctid) < (b.accepted, b.ctid);
+
+CREATE UNIQUE INDEX user_status_user_id_problem_row_id_idx ON user_status (user_id, problem_row_id);
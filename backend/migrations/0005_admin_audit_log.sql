@@ -0,0 +1,7 @@
+CREATE TABLE admin_audit_log (
+    id           SERIAL PRIMARY KEY,
+    actor        TEXT NOT NULL,
+    action       TEXT NOT NULL,
+    detail       JSONB,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
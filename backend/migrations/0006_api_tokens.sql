@@ -0,0 +1,10 @@
+CREATE TYPE api_scope AS ENUM ('read', 'room-admin', 'global-admin');
+
+CREATE TABLE api_tokens (
+    id           SERIAL PRIMARY KEY,
+    name         TEXT NOT NULL,
+    token_hash   TEXT NOT NULL UNIQUE,
+    scopes       api_scope[] NOT NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    revoked_at   TIMESTAMPTZ
+);
@@ -0,0 +1,25 @@
+CREATE TYPE webhook_format AS ENUM ('json', 'discord', 'slack');
+
+CREATE TABLE webhooks (
+    id           SERIAL PRIMARY KEY,
+    url          TEXT NOT NULL,
+    format       webhook_format NOT NULL DEFAULT 'json',
+    secret       TEXT NOT NULL,
+    room_id      TEXT REFERENCES rooms (id) ON DELETE CASCADE,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE webhook_deliveries (
+    id               SERIAL PRIMARY KEY,
+    webhook_id       INT NOT NULL REFERENCES webhooks (id) ON DELETE CASCADE,
+    payload          JSONB NOT NULL,
+    attempts         INT NOT NULL DEFAULT 0,
+    -- NULL once delivered or given up.
+    next_attempt_at  TIMESTAMPTZ DEFAULT now(),
+    delivered_at     TIMESTAMPTZ,
+    last_error       TEXT,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX webhook_deliveries_next_attempt_at_index
+    ON webhook_deliveries (next_attempt_at) WHERE next_attempt_at IS NOT NULL;
@@ -0,0 +1,10 @@
+CREATE TABLE user_streaks (
+    user_id      TEXT NOT NULL,
+    -- 'solved', 'bingo', or 'full_level_N' for the N-th level.
+    kind         TEXT NOT NULL,
+    current      INT NOT NULL,
+    longest      INT NOT NULL,
+    -- The last day counted in the current streak.
+    last_date    DATE NOT NULL,
+    PRIMARY KEY (user_id, kind)
+);
@@ -0,0 +1,16 @@
+CREATE TYPE ranking_span AS ENUM ('all_time', 'last_30_days');
+
+-- Totals of the users on the global boards, refreshed periodically by the rankings worker.
+CREATE TABLE rankings (
+    span          ranking_span NOT NULL,
+    user_id       TEXT NOT NULL,
+    bingos        INT8 NOT NULL,
+    -- Levels of the boards whose cells are all filled.
+    full_boards   INT8 NOT NULL,
+    -- The longest bingo streak for all time, and the current one for the last 30 days.
+    streak        INT NOT NULL,
+    -- Sum of the difficulties of the solved cells.
+    score         INT8 NOT NULL,
+    refreshed_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (span, user_id)
+);
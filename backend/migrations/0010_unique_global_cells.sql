@@ -0,0 +1,14 @@
+-- Keep the first cell of each position where concurrent choosers stored a board twice.
+DELETE FROM user_status USING problems a, problems b
+WHERE user_status.problem_row_id = a.id
+    AND a.room_id IS NULL AND b.room_id IS NULL
+    AND a.cadence = b.cadence AND a.chosen_date = b.chosen_date AND a.position = b.position
+    AND a.id > b.id;
+
+DELETE FROM problems a USING problems b
+WHERE a.room_id IS NULL AND b.room_id IS NULL
+    AND a.cadence = b.cadence AND a.chosen_date = b.chosen_date AND a.position = b.position
+    AND a.id > b.id;
+
+CREATE UNIQUE INDEX problems_global_cell_idx ON problems (cadence, chosen_date, position)
+WHERE room_id IS NULL;
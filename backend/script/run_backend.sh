@@ -1,4 +0,0 @@
-server &
-choose_problems &
-update_users &
-while sleep 1000; do :; done
@@ -0,0 +1,78 @@
+use anyhow::anyhow;
+use postgres_types::{FromSql, ToSql};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{fmt, str::FromStr};
+
+/// What a token is allowed to do.
+/// Each scope includes the ones before it.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSql, FromSql,
+)]
+#[serde(rename_all = "kebab-case")]
+#[postgres(name = "api_scope")]
+pub enum Scope {
+    /// Read the boards and the rooms, and join rooms.
+    #[postgres(name = "read")]
+    Read,
+    /// Create and manage rooms.
+    #[postgres(name = "room-admin")]
+    RoomAdmin,
+    /// Manage the global boards and the tokens.
+    #[postgres(name = "global-admin")]
+    GlobalAdmin,
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Scope::Read => "read",
+            Scope::RoomAdmin => "room-admin",
+            Scope::GlobalAdmin => "global-admin",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Scope {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "read" => Ok(Scope::Read),
+            "room-admin" => Ok(Scope::RoomAdmin),
+            "global-admin" => Ok(Scope::GlobalAdmin),
+            _ => Err(anyhow!("Unknown scope: {s:?}")),
+        }
+    }
+}
+
+/// Owner of the token with which a request is sent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Principal {
+    /// Recorded as the actor of the actions with the token.
+    pub name: String,
+    pub scopes: Vec<Scope>,
+}
+
+impl Principal {
+    /// Whether or not any scope of the token includes `scope`.
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.iter().any(|granted| *granted >= scope)
+    }
+}
+
+/// Hash of `token` stored in the database instead of the token itself.
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Random token to be handed to its owner only once.
+pub fn generate_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect()
+}
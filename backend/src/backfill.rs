@@ -0,0 +1,51 @@
+use crate::{
+    config::Config,
+    crawler::submissions::{AtCoderProblemsApi, SubmissionPages},
+    database::DatabaseClient,
+    updater::update_user_status,
+};
+use anyhow::Result;
+use chrono::{Duration, NaiveDate};
+
+/// Apply all submissions on the boards from `from` to `to` (inclusive).
+/// Only the user status and the streaks are updated, and nobody is notified of the old solves.
+pub async fn backfill(
+    client: &DatabaseClient,
+    config: &Config,
+    from: &NaiveDate,
+    to: &NaiveDate,
+) -> Result<()> {
+    let begin_time = config.start_of_date(from);
+    let end_time = config.start_of_date(&(*to + Duration::days(1)));
+    let total_seconds = (end_time - begin_time).num_seconds().max(1);
+
+    // A day has about 100,000 submissions at most.
+    let days = (*to - *from).num_days() as usize + 1;
+    let mut pages = SubmissionPages::new(AtCoderProblemsApi, begin_time).max_pages(days * 500);
+    let mut submission_num = 0;
+    let mut updated_num = 0;
+    while let Some(page) = pages.next_page().await? {
+        for submission in page
+            .iter()
+            .filter(|submission| submission.submission_time < end_time)
+        {
+            submission_num += 1;
+            match update_user_status(client, config, submission, false).await {
+                Ok(true) => updated_num += 1,
+                Ok(false) => {}
+                Err(e) => log::error!("Failed to update user status: {e}"),
+            }
+        }
+
+        let done_seconds = (pages.begin_time().min(end_time) - begin_time).num_seconds();
+        log::info!(
+            "Backfilled until {} ({}%): {submission_num} submissions, {updated_num} updates.",
+            pages.begin_time().min(end_time),
+            done_seconds * 100 / total_seconds,
+        );
+        if pages.begin_time() >= end_time {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use crate::feed::escape;
+
+const HEIGHT: usize = 20;
+/// Padding on each side of a text.
+const PADDING: usize = 6;
+
+/// Rough width of `text` in 11px Verdana, which is enough to lay out a badge.
+fn text_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c {
+            'i' | 'l' | 'I' | '.' | ',' | ':' | '|' | ' ' => 4,
+            'm' | 'w' | 'M' | 'W' => 10,
+            _ if c.is_ascii() => 7,
+            _ => 11,
+        })
+        .sum()
+}
+
+/// SVG badge like the ones of shields.io, with `label` on the left and `message` on the right.
+pub fn render_badge(label: &str, message: &str, color: &str) -> String {
+    let label_width = text_width(label) + 2 * PADDING;
+    let message_width = text_width(message) + 2 * PADDING;
+    let width = label_width + message_width;
+    let (label, message) = (escape(label), escape(message));
+    let label_x = label_width / 2;
+    let message_x = label_width + message_width / 2;
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{HEIGHT}\" \
+        role=\"img\" aria-label=\"{label}: {message}\">\n\
+        <title>{label}: {message}</title>\n\
+        <clipPath id=\"r\"><rect width=\"{width}\" height=\"{HEIGHT}\" rx=\"3\"/></clipPath>\n\
+        <g clip-path=\"url(#r)\">\n\
+        <rect width=\"{label_width}\" height=\"{HEIGHT}\" fill=\"#555\"/>\n\
+        <rect x=\"{label_width}\" width=\"{message_width}\" height=\"{HEIGHT}\" fill=\"{color}\"/>\n\
+        </g>\n\
+        <g fill=\"#fff\" text-anchor=\"middle\" font-family=\"Verdana,Geneva,sans-serif\" \
+        font-size=\"11\">\n\
+        <text x=\"{label_x}\" y=\"14\">{label}</text>\n\
+        <text x=\"{message_x}\" y=\"14\">{message}</text>\n\
+        </g>\n\
+        </svg>\n"
+    )
+}
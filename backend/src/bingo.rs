@@ -0,0 +1,397 @@
+use crate::{
+    crawler::problems::Problem,
+    database::models::{self, UserStatus},
+};
+use anyhow::{bail, Result};
+use chrono::{Datelike, Duration, NaiveDate};
+use postgres_types::{FromSql, ToSql};
+use rand::{prelude::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+};
+
+/// The number of problems in each level.
+pub const BINGO_SIZE: usize = 9;
+/// The number of levels in the daily bingo.
+pub const BINGO_NUM: usize = 5;
+/// The maximum number of levels in a room.
+pub const MAX_BANDS: usize = 10;
+
+/// Range of difficulties `[lower, upper)` from which problems of a level are chosen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Band {
+    pub lower: i32,
+    pub upper: i32,
+}
+
+/// Bands of the daily bingo.
+pub const DEFAULT_BANDS: [Band; BINGO_NUM] = [
+    Band {
+        lower: -10000,
+        upper: 600,
+    },
+    Band {
+        lower: 400,
+        upper: 1400,
+    },
+    Band {
+        lower: 1200,
+        upper: 2200,
+    },
+    Band {
+        lower: 2000,
+        upper: 2800,
+    },
+    Band {
+        lower: 2600,
+        upper: 10000,
+    },
+];
+
+/// Fail unless `bands` make from 1 to `MAX_BANDS` levels, each of which has some range.
+pub fn validate_bands(bands: &[Band]) -> Result<()> {
+    if bands.is_empty() || bands.len() > MAX_BANDS {
+        bail!("The number of bands must be from 1 to {MAX_BANDS}.");
+    }
+    if let Some(band) = bands.iter().find(|band| band.lower >= band.upper) {
+        bail!("The band from {} to {} is empty.", band.lower, band.upper);
+    }
+    Ok(())
+}
+
+/// How often a new board is generated.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSql, FromSql,
+)]
+#[serde(rename_all = "snake_case")]
+#[postgres(name = "board_cadence")]
+pub enum Cadence {
+    #[postgres(name = "daily")]
+    Daily,
+    /// From Monday to Sunday.
+    #[postgres(name = "weekly")]
+    Weekly,
+    #[postgres(name = "monthly")]
+    Monthly,
+}
+
+impl Cadence {
+    pub const ALL: [Cadence; 3] = [Cadence::Daily, Cadence::Weekly, Cadence::Monthly];
+
+    /// Bands of the boards.
+    /// Boards for longer periods consist of harder problems.
+    pub fn bands(self) -> Vec<Band> {
+        let offset = match self {
+            Cadence::Daily => 0,
+            Cadence::Weekly => 200,
+            Cadence::Monthly => 400,
+        };
+        DEFAULT_BANDS
+            .iter()
+            .map(|band| Band {
+                lower: band.lower + offset,
+                upper: band.upper + offset,
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Cadence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Cadence::Daily => "daily",
+            Cadence::Weekly => "weekly",
+            Cadence::Monthly => "monthly",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Period in which a board is played.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct BoardPeriod {
+    pub cadence: Cadence,
+    /// The first day of the period.
+    pub start: NaiveDate,
+    /// The last day of the period (inclusive).
+    pub end: NaiveDate,
+}
+
+impl BoardPeriod {
+    /// The period of `cadence` which contains `date`.
+    pub fn containing(cadence: Cadence, date: NaiveDate) -> Self {
+        let (start, end) = match cadence {
+            Cadence::Daily => (date, date),
+            Cadence::Weekly => {
+                let start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+                (start, start + Duration::days(6))
+            }
+            Cadence::Monthly => {
+                let start = date.with_day(1).unwrap();
+                let next_start = if date.month() == 12 {
+                    NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)
+                };
+                (start, next_start.unwrap().pred_opt().unwrap())
+            }
+        };
+        Self {
+            cadence,
+            start,
+            end,
+        }
+    }
+
+    /// The periods of `cadence` which contain any day from `from` to `to` (inclusive).
+    pub fn covering(cadence: Cadence, from: NaiveDate, to: NaiveDate) -> Vec<Self> {
+        let mut periods = Vec::new();
+        let mut date = from;
+        while date <= to {
+            let period = Self::containing(cadence, date);
+            date = period.end + Duration::days(1);
+            periods.push(period);
+        }
+        periods
+    }
+}
+
+/// Choose `BINGO_SIZE` problems randomly for each band.
+/// `problems` must be sorted by their difficulties.
+///
+/// If a band contains less than `BINGO_SIZE` problems, all of them are chosen.
+pub fn choose_problems<R: Rng>(problems: &[Problem], bands: &[Band], rng: &mut R) -> Vec<Problem> {
+    let mut bingo_problems = Vec::new();
+    for band in bands {
+        // `lower_index` = the leftmost problem with difficulty >= `band.lower`.
+        // `upper_index` = the rightmost problem with difficulty >= `band.upper`.
+        // Problems whose difficulty is in [lower, upper) is in [lower_index, upper_index).
+        // Double the difficulties so that any problems doesn't match and we can detect the precise border.
+        let lower_index = problems
+            .binary_search_by_key(&(band.lower * 2 + 1), |problem| problem.difficulty * 2)
+            .unwrap_or_else(|i| i);
+        let upper_index = problems
+            .binary_search_by_key(&(band.upper * 2 + 1), |problem| problem.difficulty * 2)
+            .unwrap_or_else(|i| i);
+
+        // Choose problems randomly.
+        let mut indices: Vec<usize> = (lower_index..upper_index).collect();
+        let (chosen_indices, _) = indices.partial_shuffle(rng, BINGO_SIZE);
+        let mut bingo = chosen_indices
+            .iter_mut()
+            .map(|index| problems[*index].clone())
+            .collect();
+
+        bingo_problems.append(&mut bingo);
+    }
+    bingo_problems
+}
+
+/// Choose a problem in `band` randomly except those in `excluded_ids`.
+/// Return `None` if there are no such problems.
+pub fn choose_replacement<R: Rng>(
+    problems: &[Problem],
+    band: &Band,
+    excluded_ids: &[&str],
+    rng: &mut R,
+) -> Option<Problem> {
+    let candidates: Vec<&Problem> = problems
+        .iter()
+        .filter(|problem| band.lower <= problem.difficulty && problem.difficulty < band.upper)
+        .filter(|problem| !excluded_ids.contains(&problem.problem_id.as_str()))
+        .collect();
+    candidates.choose(rng).map(|problem| (*problem).clone())
+}
+
+/// Lines of a level as indices of the cells, which are arranged in a 3x3 grid.
+pub const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+/// The number of completed lines in each level.
+/// `filled[position]` is whether or not the cell at `position` is filled.
+pub fn count_lines(filled: &[bool]) -> Vec<usize> {
+    filled
+        .chunks(BINGO_SIZE)
+        .map(|level| {
+            LINES
+                .iter()
+                .filter(|line| line.iter().all(|&cell| level.get(cell) == Some(&true)))
+                .count()
+        })
+        .collect()
+}
+
+/// The number of completed lines through the cell at `position`.
+/// `filled[position]` is whether or not the cell at `position` is filled.
+pub fn count_lines_through(filled: &[bool], position: usize) -> usize {
+    let level_start = position / BINGO_SIZE * BINGO_SIZE;
+    let level = &filled[level_start..filled.len().min(level_start + BINGO_SIZE)];
+    LINES
+        .iter()
+        .filter(|line| line.contains(&(position - level_start)))
+        .filter(|line| line.iter().all(|&cell| level.get(cell) == Some(&true)))
+        .count()
+}
+
+/// Board of a side (a team or a user) in a room.
+#[derive(Clone, Debug, Serialize)]
+pub struct SideBoard {
+    pub side: String,
+    /// Whether or not the cell at each position is filled by this side.
+    pub filled: Vec<bool>,
+    /// The number of completed lines in each level.
+    pub lines: Vec<usize>,
+}
+
+/// Fill the board of each side.
+///
+/// `sides` maps each user to their side, and users not in `sides` are ignored.
+/// If `claim_first` is set, only the side with the earliest AC fills each cell.
+/// Otherwise, every side with an AC fills it.
+pub fn fill_boards(
+    problems: &[models::Problem],
+    sides: &HashMap<String, String>,
+    user_status: &[UserStatus],
+    claim_first: bool,
+) -> Vec<SideBoard> {
+    let positions: HashMap<i32, usize> = problems
+        .iter()
+        .enumerate()
+        .map(|(position, problem)| (problem.id, position))
+        .collect();
+
+    // Collect the accepted status of each cell.
+    let mut accepted: Vec<Vec<&UserStatus>> = vec![Vec::new(); problems.len()];
+    for status in user_status.iter().filter(|status| status.accepted) {
+        if let (Some(&position), true) = (
+            positions.get(&status.problem_row_id),
+            sides.contains_key(&status.user_id),
+        ) {
+            accepted[position].push(status);
+        }
+    }
+
+    let side_names: BTreeSet<&String> = sides.values().collect();
+    let mut boards: BTreeMap<&String, Vec<bool>> = side_names
+        .into_iter()
+        .map(|side| (side, vec![false; problems.len()]))
+        .collect();
+
+    for (position, statuses) in accepted.iter().enumerate() {
+        if claim_first {
+            let first = statuses.iter().copied().reduce(|first, status| {
+                if status.accepted_before(first) {
+                    status
+                } else {
+                    first
+                }
+            });
+            if let Some(status) = first {
+                boards.get_mut(&sides[&status.user_id]).unwrap()[position] = true;
+            }
+        } else {
+            for status in statuses {
+                boards.get_mut(&sides[&status.user_id]).unwrap()[position] = true;
+            }
+        }
+    }
+
+    boards
+        .into_iter()
+        .map(|(side, filled)| SideBoard {
+            side: side.clone(),
+            lines: count_lines(&filled),
+            filled,
+        })
+        .collect()
+}
+
+/// Side of each member of a room in `mode`, which is their team in team modes or themselves otherwise.
+pub fn room_sides(
+    mode: models::RoomMode,
+    members: &[models::RoomMember],
+) -> HashMap<String, String> {
+    members
+        .iter()
+        .filter_map(|member| {
+            if mode.is_team_mode() {
+                let team = member.team.clone()?;
+                Some((member.user_id.clone(), team))
+            } else {
+                Some((member.user_id.clone(), member.user_id.clone()))
+            }
+        })
+        .collect()
+}
+
+/// Cells of a room in `mode` filled by the side of `user_id`.
+/// No cell is filled if they are not a member.
+pub fn filled_by_side_of(
+    mode: models::RoomMode,
+    members: &[models::RoomMember],
+    problems: &[models::Problem],
+    user_status: &[UserStatus],
+    user_id: &str,
+) -> Vec<bool> {
+    let sides = room_sides(mode, members);
+    let Some(side) = sides.get(user_id) else {
+        return vec![false; problems.len()];
+    };
+    fill_boards(problems, &sides, user_status, mode.is_claim_first())
+        .into_iter()
+        .find(|board| &board.side == side)
+        .map(|board| board.filled)
+        .unwrap_or_else(|| vec![false; problems.len()])
+}
+
+/// Standing of a side in a room.
+#[derive(Clone, Debug, Serialize)]
+pub struct Standing {
+    pub rank: usize,
+    pub side: String,
+    /// The number of filled cells.
+    pub cells: usize,
+    /// The number of completed lines in all levels.
+    pub lines: usize,
+}
+
+/// Rank sides by the number of filled cells, and then by the number of lines.
+/// Sides with the same score share the same rank.
+pub fn standings(boards: &[SideBoard]) -> Vec<Standing> {
+    let mut standings: Vec<Standing> = boards
+        .iter()
+        .map(|board| Standing {
+            rank: 0,
+            side: board.side.clone(),
+            cells: board.filled.iter().filter(|&&filled| filled).count(),
+            lines: board.lines.iter().sum(),
+        })
+        .collect();
+    standings.sort_by(|a, b| {
+        (b.cells, b.lines)
+            .cmp(&(a.cells, a.lines))
+            .then_with(|| a.side.cmp(&b.side))
+    });
+
+    for i in 0..standings.len() {
+        standings[i].rank = if i > 0
+            && (standings[i - 1].cells, standings[i - 1].lines)
+                == (standings[i].cells, standings[i].lines)
+        {
+            standings[i - 1].rank
+        } else {
+            i + 1
+        };
+    }
+    standings
+}
@@ -0,0 +1,95 @@
+use crate::{bingo::BINGO_SIZE, database::models::Problem};
+use chrono::{DateTime, Utc};
+
+/// Event in a calendar for a board or a room.
+pub struct CalendarEvent {
+    /// Unique among all calendars so that updates replace the event.
+    pub uid: String,
+    pub summary: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Link to the board.
+    pub url: String,
+    /// Problems in the order of the positions.
+    pub problems: Vec<Problem>,
+}
+
+/// Escape a TEXT value of RFC 5545.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Fold a content line into lines of at most 75 octets, and terminate it with CRLF.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            // The leading space counts.
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Problems of each level as plain text.
+fn describe(event: &CalendarEvent) -> String {
+    let mut text = event.url.clone();
+    for (level, problems) in event.problems.chunks(BINGO_SIZE).enumerate() {
+        text += &format!("\n\nLevel {}", level + 1);
+        for problem in problems {
+            text += &format!(
+                "\n- {} ({}) {}",
+                problem.title,
+                problem.difficulty,
+                problem.url()
+            );
+        }
+    }
+    text
+}
+
+/// iCalendar with `events`.
+/// `stamp` is when the calendar is generated.
+pub fn render_ics(name: &str, events: &[CalendarEvent], stamp: &DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//AtCoder Bingo//Boards//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape(name)),
+    ];
+    for event in events {
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}", event.uid),
+            format!("DTSTAMP:{}", format_time(stamp)),
+            format!("DTSTART:{}", format_time(&event.start)),
+            format!("DTEND:{}", format_time(&event.end)),
+            format!("SUMMARY:{}", escape(&event.summary)),
+            format!("URL:{}", event.url),
+            format!("DESCRIPTION:{}", escape(&describe(event))),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold(line)).collect()
+}
@@ -0,0 +1,159 @@
+use crate::{
+    bingo::{self, BoardPeriod, Cadence},
+    config::Config,
+    crawler::problems::{get_problems, Problem},
+    database::{models, DatabaseClient},
+    events::BoardKey,
+    metrics::metrics,
+    webhook::{self, Notification},
+};
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use std::time::Instant;
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+
+async fn choose_problems(cadence: Cadence) -> Result<Vec<Problem>> {
+    // Fetch problems and sort by difficulties.
+    let mut problems = get_problems().await?;
+    problems.sort_by_key(|problem| problem.difficulty);
+
+    // `ThreadRng` must not be held across `await` to run on any thread.
+    let mut rng = rand::thread_rng();
+
+    Ok(bingo::choose_problems(
+        &problems,
+        &cadence.bands(),
+        &mut rng,
+    ))
+}
+
+fn to_entities(problems: &[Problem], period: &BoardPeriod) -> Vec<models::Problem> {
+    problems
+        .iter()
+        .enumerate()
+        .map(|(position, problem)| models::Problem {
+            id: 0,
+            chosen_date: period.start,
+            cadence: period.cadence,
+            period_end: period.end,
+            position: position as i32,
+            problem_id: problem.problem_id.clone(),
+            contest_id: problem.contest_id.clone(),
+            title: problem.title.clone(),
+            difficulty: problem.difficulty,
+            room_id: None,
+        })
+        .collect()
+}
+
+/// Replace the board for `period` with newly chosen problems, recording `audit` with it.
+/// The user status for the old board is deleted.
+pub async fn regenerate(
+    client: &DatabaseClient,
+    period: &BoardPeriod,
+    audit: &models::NewAuditEntry,
+) -> Result<()> {
+    let problems = choose_problems(period.cadence).await?;
+    client
+        .replace_problems_by_period(period, &to_entities(&problems, period), audit)
+        .await
+}
+
+/// Choose problems if they have not been chosen for `period`, and notify the webhooks.
+/// Return whether or not problems are chosen.
+async fn choose_and_store_problems(client: &DatabaseClient, period: &BoardPeriod) -> Result<bool> {
+    // Check if the bingo of this period already exists.
+    if !client.select_problems_by_period(period).await?.is_empty() {
+        return Ok(false);
+    }
+
+    // Generate and store bingo.
+    let problems = choose_problems(period.cadence).await?;
+    if !client
+        .insert_problems(&to_entities(&problems, period))
+        .await?
+    {
+        log::info!(
+            "The {} bingo from {} has been chosen by another process.",
+            period.cadence,
+            period.start
+        );
+        return Ok(false);
+    }
+
+    let notification = Notification::BoardCreated {
+        board: BoardKey {
+            cadence: period.cadence,
+            chosen_date: period.start,
+            room_id: None,
+        },
+        period_end: period.end,
+        problems: problems.iter().map(Into::into).collect(),
+    };
+    if let Err(e) = webhook::enqueue(client, &notification).await {
+        log::error!("Failed to notify the webhooks of the new board: {e}");
+    }
+    Ok(true)
+}
+
+/// Record the time to generate a board since `started_at`.
+fn observe_generation(cadence: Cadence, result: &str, started_at: Instant) {
+    metrics()
+        .board_generation_seconds
+        .with_label_values(&[&cadence.to_string(), result])
+        .observe(started_at.elapsed().as_secs_f64());
+}
+
+/// Generate the boards of each cadence until `config.days_ahead` days later
+/// unless they exist.
+async fn prepare_boards(client: &DatabaseClient, config: &Config) {
+    let today = config.today();
+    let last_date = today + Duration::days(config.days_ahead as i64);
+    for cadence in Cadence::ALL {
+        for period in BoardPeriod::covering(cadence, today, last_date) {
+            let started_at = Instant::now();
+            match choose_and_store_problems(client, &period).await {
+                Ok(true) => {
+                    observe_generation(cadence, "ok", started_at);
+                    log::info!("New {cadence:?} bingo from {} is generated.", period.start);
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    observe_generation(cadence, "error", started_at);
+                    log::error!(
+                        "Failed to generate {cadence:?} bingo from {}: {}",
+                        period.start,
+                        e
+                    );
+                }
+            }
+        }
+    }
+    log::info!("Finished to prepare boards until {last_date}.");
+}
+
+/// Prepare boards at once to catch up on the runs missed while stopped,
+/// and then on the schedule of `config` until `token` is cancelled.
+/// A board being generated is stored before returning.
+pub async fn run(client: &DatabaseClient, config: &Config, token: &CancellationToken) {
+    let mut last_run = Utc::now();
+    while !token.is_cancelled() {
+        prepare_boards(client, config).await;
+
+        let Some(next_run) = config.next_choose_time(&last_run) else {
+            log::error!("The schedule to generate boards never comes.");
+            token.cancelled().await;
+            return;
+        };
+        log::info!("Next boards will be prepared at {next_run}.");
+
+        // A run which is overdue, e.g. after the machine sleeps, starts immediately.
+        let delay = (next_run - Utc::now()).to_std().unwrap_or_default();
+        tokio::select! {
+            _ = sleep(delay) => {}
+            _ = token.cancelled() => {}
+        }
+        last_run = next_run;
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{
+    auth::{hash_token, Principal, Scope},
+    bingo::Cadence,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+use std::{env, fmt, str::FromStr};
+
+/// Timezone in which boards switch if `BINGO_TIMEZONE` is not set.
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::Asia::Tokyo;
+
+/// Boards are generated at 23:50 if `BINGO_CHOOSE_SCHEDULE` is not set.
+/// The fields are sec, min, hour, day of month, month and day of week.
+pub const DEFAULT_CHOOSE_SCHEDULE: &str = "0 50 23 * * *";
+
+/// Boards are generated for tomorrow if `BINGO_DAYS_AHEAD` is not set.
+pub const DEFAULT_DAYS_AHEAD: u32 = 1;
+
+/// Pages of the boards and the rooms are linked under this URL if `BINGO_FRONTEND_URL` is not set.
+pub const DEFAULT_FRONTEND_URL: &str = "http://localhost:8080";
+
+/// Bearer token given in the config instead of the database.
+/// Only the hash of the token is kept, like the tokens in the database.
+#[derive(Clone)]
+pub struct StaticToken {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub token_hash: String,
+}
+
+impl fmt::Debug for StaticToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the token itself.
+        f.debug_struct("StaticToken")
+            .field("name", &self.name)
+            .field("scopes", &self.scopes)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FromStr for StaticToken {
+    type Err = anyhow::Error;
+
+    /// Parse `name:scopes:token`, where `scopes` are joined with `+`.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.splitn(3, ':');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(name), Some(scopes), Some(token)) if !name.is_empty() && !token.is_empty() => {
+                Ok(Self {
+                    name: name.to_string(),
+                    scopes: scopes
+                        .split('+')
+                        .map(Scope::from_str)
+                        .collect::<Result<_>>()?,
+                    token_hash: hash_token(token),
+                })
+            }
+            _ => Err(anyhow!("A token must be in the form of name:scopes:token.")),
+        }
+    }
+}
+
+/// Settings shared by the server and the background workers.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Timezone in which a day of the boards starts and ends.
+    pub timezone: Tz,
+    /// Cron expression in `timezone` of when the boards are generated.
+    pub choose_schedule: Schedule,
+    /// Boards are generated until this many days later.
+    pub days_ahead: u32,
+    /// Tokens accepted in addition to those in the database.
+    pub static_tokens: Vec<StaticToken>,
+    /// Base URL of the frontend, without the trailing slash.
+    pub frontend_url: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            timezone: DEFAULT_TIMEZONE,
+            choose_schedule: Schedule::from_str(DEFAULT_CHOOSE_SCHEDULE).unwrap(),
+            days_ahead: DEFAULT_DAYS_AHEAD,
+            static_tokens: Vec::new(),
+            frontend_url: DEFAULT_FRONTEND_URL.to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// Read settings from the environment variables.
+    pub fn from_env() -> Result<Self> {
+        let timezone = match env::var("BINGO_TIMEZONE") {
+            Ok(name) => name
+                .parse()
+                .map_err(|e| anyhow!("Invalid BINGO_TIMEZONE {name:?}: {e}"))?,
+            Err(_) => DEFAULT_TIMEZONE,
+        };
+        let choose_schedule = match env::var("BINGO_CHOOSE_SCHEDULE") {
+            Ok(expression) => Schedule::from_str(&expression)
+                .map_err(|e| anyhow!("Invalid BINGO_CHOOSE_SCHEDULE {expression:?}: {e}"))?,
+            Err(_) => Schedule::from_str(DEFAULT_CHOOSE_SCHEDULE).unwrap(),
+        };
+        let days_ahead = match env::var("BINGO_DAYS_AHEAD") {
+            Ok(days) => days
+                .parse()
+                .map_err(|e| anyhow!("Invalid BINGO_DAYS_AHEAD {days:?}: {e}"))?,
+            Err(_) => DEFAULT_DAYS_AHEAD,
+        };
+        // Comma-separated `name:scopes:token`.
+        let static_tokens = match env::var("BINGO_API_TOKENS") {
+            Ok(tokens) => tokens
+                .split(',')
+                .filter(|token| !token.is_empty())
+                .map(StaticToken::from_str)
+                .collect::<Result<_>>()
+                .map_err(|e| anyhow!("Invalid BINGO_API_TOKENS: {e}"))?,
+            Err(_) => Vec::new(),
+        };
+        let frontend_url = env::var("BINGO_FRONTEND_URL")
+            .unwrap_or_else(|_| DEFAULT_FRONTEND_URL.to_string())
+            .trim_end_matches('/')
+            .to_string();
+        Ok(Self {
+            timezone,
+            choose_schedule,
+            days_ahead,
+            static_tokens,
+            frontend_url,
+        })
+    }
+
+    /// Date of the boards at `time`.
+    pub fn board_date(&self, time: &DateTime<Utc>) -> NaiveDate {
+        time.with_timezone(&self.timezone).date_naive()
+    }
+
+    /// Date of the boards now.
+    pub fn today(&self) -> NaiveDate {
+        self.board_date(&Utc::now())
+    }
+
+    /// The first time to generate boards strictly after `time`.
+    pub fn next_choose_time(&self, time: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.choose_schedule
+            .after(&time.with_timezone(&self.timezone))
+            .next()
+            .map(|time| time.with_timezone(&Utc))
+    }
+
+    /// Page of the global board of `cadence` starting on `date` in the frontend.
+    pub fn board_page_url(&self, cadence: Cadence, date: &NaiveDate) -> String {
+        format!("{}/?cadence={cadence}&date={date}", self.frontend_url)
+    }
+
+    /// Page of the room in the frontend.
+    pub fn room_page_url(&self, room_id: &str) -> String {
+        format!("{}/?room={room_id}", self.frontend_url)
+    }
+
+    /// Owner of `token` if it is one of the static tokens.
+    pub fn static_principal(&self, token: &str) -> Option<Principal> {
+        // Compare the hashes so that the time does not depend on the token.
+        let token_hash = hash_token(token);
+        self.static_tokens
+            .iter()
+            .find(|static_token| static_token.token_hash == token_hash)
+            .map(|static_token| Principal {
+                name: static_token.name.clone(),
+                scopes: static_token.scopes.clone(),
+            })
+    }
+
+    /// The moment when the boards of `date` start.
+    pub fn start_of_date(&self, date: &NaiveDate) -> DateTime<Utc> {
+        // Midnight may be skipped by the daylight saving time.
+        (0..24)
+            .find_map(|hour| {
+                let time = date.and_hms_opt(hour, 0, 0).unwrap();
+                self.timezone.from_local_datetime(&time).earliest()
+            })
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+}
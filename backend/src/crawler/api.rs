@@ -1,11 +1,30 @@
+use crate::metrics::metrics;
 use anyhow::Result;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::time::sleep;
 
 /// Send GET request and return its response.
 /// Wait 5 secs after querying.
 pub async fn get_request(url: &str) -> Result<String> {
-    let body = reqwest::get(url).await?.text().await?;
+    let started_at = Instant::now();
+    let result = fetch(url).await;
+    let status = match &result {
+        Ok((status, _)) => status.as_str(),
+        Err(_) => "error",
+    };
+    metrics()
+        .crawler_request_seconds
+        .with_label_values(&[status])
+        .observe(started_at.elapsed().as_secs_f64());
+
+    let (_, body) = result?;
+    metrics().crawler_response_bytes.inc_by(body.len() as u64);
     sleep(Duration::from_secs(5)).await;
     Ok(body)
 }
+
+async fn fetch(url: &str) -> Result<(reqwest::StatusCode, String)> {
+    let response = reqwest::get(url).await?;
+    let status = response.status();
+    Ok((status, response.text().await?))
+}
@@ -1,7 +1,13 @@
-use crate::crawler::api::get_request;
+use crate::{crawler::api::get_request, metrics::metrics};
 use anyhow::Result;
-use chrono::{Duration, Local, NaiveDateTime};
+use chrono::{DateTime, Duration, Utc};
 use serde::Deserialize;
+use std::{collections::HashSet, future::Future};
+
+/// The maximum number of submissions the API returns at once.
+pub const PAGE_SIZE: usize = 1000;
+/// The number of pages fetched at most by default.
+pub const MAX_PAGES: usize = 100;
 
 #[derive(Debug, Deserialize)]
 struct RawSubmission {
@@ -15,29 +21,23 @@ struct RawSubmission {
 #[derive(Debug)]
 pub struct Submission {
     pub id: u32,
-    pub submission_time: NaiveDateTime,
+    pub submission_time: DateTime<Utc>,
     pub problem_id: String,
     pub user_id: String,
     pub is_accepted: bool,
 }
 
-/// Fetch at most 1000 submissions from `begin_time`.
-async fn get_submissions_from(begin_time: &NaiveDateTime) -> Result<Vec<Submission>> {
-    let begin_time_epoch = begin_time.timestamp();
-
-    // Fetch raw submissions.
-    let body = get_request(&format!(
-        "https://kenkoooo.com/atcoder/atcoder-api/v3/from/{begin_time_epoch}"
-    ))
-    .await?;
-    let raw_submissions: Vec<RawSubmission> = serde_json::from_str(&body)?;
+/// Parse a response of the submission API.
+pub fn parse_submissions(body: &str) -> Result<Vec<Submission>> {
+    let raw_submissions: Vec<RawSubmission> = serde_json::from_str(body)?;
 
     // Convert into `Submission`.
     let submissions = raw_submissions
         .iter()
         .map(|raw_submission| {
-            // epoch to NaiveDateTime
-            let submission_time = NaiveDateTime::from_timestamp(raw_submission.epoch_second, 0);
+            // epoch to DateTime
+            let submission_time =
+                DateTime::from_timestamp(raw_submission.epoch_second, 0).unwrap_or_default();
             Submission {
                 id: raw_submission.id,
                 submission_time,
@@ -50,32 +50,151 @@ async fn get_submissions_from(begin_time: &NaiveDateTime) -> Result<Vec<Submissi
     Ok(submissions)
 }
 
-/// Fetch all submissions in these `minutes` minutes.
-pub async fn get_recent_submissions(minutes: i64) -> Result<Vec<Submission>> {
-    let now = Local::now().naive_local();
-    let mut begin_time = now.checked_sub_signed(Duration::minutes(minutes)).unwrap();
+/// Where pages of submissions come from.
+pub trait SubmissionSource {
+    /// Fetch at most `PAGE_SIZE` submissions submitted at `from_second` (epoch) or later.
+    fn get_submissions_from(
+        &self,
+        from_second: i64,
+    ) -> impl Future<Output = Result<Vec<Submission>>> + Send;
+}
 
-    let mut submissions = Vec::new();
-    loop {
-        log::info!("Fetching submissions from {begin_time:?}...");
+/// Submission API of AtCoder Problems.
+pub struct AtCoderProblemsApi;
+
+impl SubmissionSource for AtCoderProblemsApi {
+    async fn get_submissions_from(&self, from_second: i64) -> Result<Vec<Submission>> {
+        let body = get_request(&format!(
+            "https://kenkoooo.com/atcoder/atcoder-api/v3/from/{from_second}"
+        ))
+        .await?;
+        parse_submissions(&body)
+    }
+}
+
+/// Pages of submissions from a certain time, fetched one by one.
+///
+/// The API can only be queried by a submission time in seconds,
+/// so each page starts from the newest second of the previous page.
+/// Submissions in that second are fetched twice and dropped by their IDs.
+pub struct SubmissionPages<S> {
+    source: S,
+    from_second: i64,
+    /// IDs of the fetched submissions submitted at `from_second`.
+    boundary_ids: HashSet<u32>,
+    page_size: usize,
+    /// The number of pages which can still be fetched.
+    remaining_pages: usize,
+    finished: bool,
+}
+
+impl<S: SubmissionSource> SubmissionPages<S> {
+    pub fn new(source: S, begin_time: DateTime<Utc>) -> Self {
+        Self {
+            source,
+            from_second: begin_time.timestamp(),
+            boundary_ids: HashSet::new(),
+            page_size: PAGE_SIZE,
+            remaining_pages: MAX_PAGES,
+            finished: false,
+        }
+    }
+
+    /// Stop after fetching `max_pages` pages even if newer submissions remain.
+    pub fn max_pages(mut self, max_pages: usize) -> Self {
+        self.remaining_pages = max_pages;
+        self
+    }
+
+    /// The number of submissions in a full page.
+    /// It should be changed only for sources other than the API.
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Time from which the next page starts.
+    pub fn begin_time(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.from_second, 0).unwrap_or_default()
+    }
+
+    /// Fetch the next page without submissions in the previous pages.
+    /// Return `None` after the page with the latest submissions.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<Submission>>> {
+        if self.finished {
+            return Ok(None);
+        }
+        if self.remaining_pages == 0 {
+            log::warn!(
+                "Stop fetching submissions at {:?} since too many pages are fetched.",
+                self.begin_time()
+            );
+            self.finished = true;
+            return Ok(None);
+        }
+        self.remaining_pages -= 1;
+
+        log::info!("Fetching submissions from {:?}...", self.begin_time());
+
+        let submissions = self.source.get_submissions_from(self.from_second).await?;
+        let submission_num = submissions.len();
+        if submission_num < self.page_size {
+            self.finished = true;
+        }
 
-        let mut recent_submissions = get_submissions_from(&begin_time).await?;
+        // Drop submissions which are already fetched in the previous page.
+        let new_submissions: Vec<Submission> = submissions
+            .into_iter()
+            .filter(|submission| !self.boundary_ids.contains(&submission.id))
+            .collect();
 
-        // Stash several information before `recent_submissions` is moved.
-        let submission_num = recent_submissions.len();
-        begin_time = recent_submissions
+        // Move the boundary to the newest second of this page.
+        let newest_second = new_submissions
             .iter()
-            .map(|submission| submission.submission_time)
+            .map(|submission| submission.submission_time.timestamp())
             .max()
-            .unwrap_or(now);
+            .unwrap_or(self.from_second);
+        if newest_second > self.from_second {
+            self.from_second = newest_second;
+            self.boundary_ids = new_submissions
+                .iter()
+                .filter(|submission| submission.submission_time.timestamp() == newest_second)
+                .map(|submission| submission.id)
+                .collect();
+        } else if !self.finished {
+            // The same page would be fetched forever.
+            // Skip the second since the rest of it can never be fetched.
+            log::warn!(
+                "Skip the rest of submissions at {:?} since more than {} submissions share the second.",
+                self.begin_time(),
+                self.page_size
+            );
+            self.from_second += 1;
+            self.boundary_ids.clear();
+        }
 
-        submissions.append(&mut recent_submissions);
+        log::info!(
+            "{submission_num} submissions are obtained, and {} of them are new.",
+            new_submissions.len()
+        );
+        Ok(Some(new_submissions))
+    }
+}
 
-        log::info!("{submission_num} submissions are obtained.");
-        if submission_num < 1000 {
-            break;
-        }
+/// Fetch all submissions in these `minutes` minutes.
+pub async fn get_recent_submissions(minutes: i64) -> Result<Vec<Submission>> {
+    let mut pages =
+        SubmissionPages::new(AtCoderProblemsApi, Utc::now() - Duration::minutes(minutes));
+
+    let mut submissions = Vec::new();
+    while let Some(mut page) = pages.next_page().await? {
+        metrics().submissions_per_page.observe(page.len() as f64);
+        submissions.append(&mut page);
     }
 
+    // Set even if no submission is found, so that quiet periods are not taken as stalls.
+    metrics()
+        .last_crawl_timestamp_seconds
+        .set(Utc::now().timestamp_millis() as f64 / 1000.0);
     Ok(submissions)
 }
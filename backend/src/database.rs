@@ -1,56 +1,279 @@
+mod api_token;
+mod audit;
+mod migration;
 pub mod models;
 mod problem;
+mod ranking;
+mod room;
+mod stats;
+mod streak;
 mod user_status;
+mod webhook;
 
-use anyhow::Result;
-use chrono::NaiveDate;
-use models::{Problem, UserStatus};
+use crate::{
+    auth::Scope,
+    bingo::BoardPeriod,
+    ranking::{RankingSort, RankingSpan},
+};
+use anyhow::{bail, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use deadpool_postgres::{Manager, ManagerConfig, Object, Pool, RecyclingMethod};
+use futures_util::{stream, StreamExt};
+use models::{
+    ApiToken, AuditEntry, LevelStats, NewAuditEntry, Problem, RankingEntry, Room, RoomMember,
+    UserStatus, UserStreak, Webhook, WebhookDelivery, WebhookFormat,
+};
 use std::{env, time::Duration};
 use tokio::time::sleep;
-use tokio_postgres::{Client, NoTls};
+use tokio_postgres::{AsyncMessage, NoTls};
 
+/// Pool of connections to the database.
+/// Clones share the same pool.
+#[derive(Clone)]
 pub struct DatabaseClient {
-    client: Client,
+    pool: Pool,
 }
 
 impl DatabaseClient {
     pub async fn new() -> Self {
         Self {
-            client: get_connection().await,
+            pool: get_pool().await,
         }
     }
 
+    async fn client(&self) -> Result<Object> {
+        Ok(self.pool.get().await?)
+    }
+
+    /// Apply migrations which have not been applied yet.
+    /// Return the versions of the applied migrations.
+    pub async fn migrate(&self) -> Result<Vec<i32>> {
+        migration::migrate(&mut self.client().await?).await
+    }
+
+    /// Send `payload` to the listeners of `channel`.
+    pub async fn notify(&self, channel: &str, payload: &str) -> Result<()> {
+        self.client()
+            .await?
+            .execute("SELECT pg_notify($1, $2)", &[&channel, &payload])
+            .await?;
+        Ok(())
+    }
+
     // Problems
-    pub async fn insert_problem(&self, problem: &Problem) -> Result<()> {
-        problem::insert(&self.client, problem).await
+    pub async fn insert_problems(&self, problems: &[Problem]) -> Result<bool> {
+        problem::insert_all(&mut **self.client().await?, problems).await
+    }
+
+    pub async fn replace_problems_by_period(
+        &self,
+        period: &BoardPeriod,
+        problems: &[Problem],
+        audit: &NewAuditEntry,
+    ) -> Result<()> {
+        problem::replace_period(&mut **self.client().await?, period, problems, audit).await
+    }
+
+    pub async fn delete_problems_by_period(
+        &self,
+        period: &BoardPeriod,
+        audit: &NewAuditEntry,
+    ) -> Result<u64> {
+        problem::delete_by_period(&mut **self.client().await?, period, audit).await
+    }
+
+    pub async fn update_problem(&self, problem: &Problem, audit: &NewAuditEntry) -> Result<()> {
+        problem::update(&mut **self.client().await?, problem, audit).await
+    }
+
+    pub async fn select_problems_by_period(&self, period: &BoardPeriod) -> Result<Vec<Problem>> {
+        problem::select_by_period(&**self.client().await?, period).await
     }
 
-    pub async fn select_problems_by_chosen_date(
+    pub async fn select_recent_daily_problems(
         &self,
-        chosen_date: &NaiveDate,
+        until: &NaiveDate,
+        days: i64,
     ) -> Result<Vec<Problem>> {
-        problem::select_by_chosen_date(&self.client, chosen_date).await
+        problem::select_recent_daily(&**self.client().await?, until, days).await
+    }
+
+    pub async fn select_global_problems_since(&self, since: &NaiveDate) -> Result<Vec<Problem>> {
+        problem::select_global_since(&**self.client().await?, since).await
+    }
+
+    pub async fn select_solved_global_problems(&self, user_id: &str) -> Result<Vec<Problem>> {
+        problem::select_solved_global(&**self.client().await?, user_id).await
     }
 
-    pub async fn select_problem_by_chosen_date_and_id(
+    pub async fn select_problems_by_date_and_id(
         &self,
-        chosen_date: &NaiveDate,
+        date: &NaiveDate,
         problem_id: &str,
-    ) -> Result<Option<Problem>> {
-        problem::select_by_chosen_date_and_id(&self.client, chosen_date, problem_id).await
+    ) -> Result<Vec<Problem>> {
+        problem::select_by_date_and_id(&**self.client().await?, date, problem_id).await
+    }
+
+    pub async fn select_problems_by_room_id(&self, room_id: &str) -> Result<Vec<Problem>> {
+        problem::select_by_room_id(&**self.client().await?, room_id).await
+    }
+
+    pub async fn select_problems_in_open_rooms_of_user(
+        &self,
+        user_id: &str,
+        problem_id: &str,
+        submission_time: &DateTime<Utc>,
+    ) -> Result<Vec<Problem>> {
+        problem::select_in_open_rooms_of_user(
+            &**self.client().await?,
+            user_id,
+            problem_id,
+            submission_time,
+        )
+        .await
+    }
+
+    // API tokens
+    pub async fn insert_api_token(
+        &self,
+        name: &str,
+        token_hash: &str,
+        scopes: &[Scope],
+    ) -> Result<i32> {
+        api_token::insert(&**self.client().await?, name, token_hash, scopes).await
+    }
+
+    pub async fn select_active_api_token_by_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<ApiToken>> {
+        api_token::select_active_by_hash(&**self.client().await?, token_hash).await
+    }
+
+    pub async fn select_api_tokens(&self) -> Result<Vec<ApiToken>> {
+        api_token::select_all(&**self.client().await?).await
+    }
+
+    pub async fn revoke_api_token(&self, id: i32) -> Result<bool> {
+        api_token::revoke(&**self.client().await?, id).await
+    }
+
+    // Audit log
+    pub async fn insert_audit_entry(&self, entry: &NewAuditEntry) -> Result<()> {
+        audit::insert(&**self.client().await?, entry).await
+    }
+
+    pub async fn select_newest_audit_entries(&self, limit: i64) -> Result<Vec<AuditEntry>> {
+        audit::select_newest(&**self.client().await?, limit).await
+    }
+
+    // Rooms
+    pub async fn insert_room(
+        &self,
+        room: &Room,
+        members: &[RoomMember],
+        problems: &[Problem],
+    ) -> Result<()> {
+        room::insert(&mut **self.client().await?, room, members, problems).await
+    }
+
+    pub async fn select_room(&self, room_id: &str) -> Result<Option<Room>> {
+        room::select(&**self.client().await?, room_id).await
+    }
+
+    pub async fn update_room_window(&self, room: &Room) -> Result<()> {
+        room::update_window(&**self.client().await?, room).await
+    }
+
+    pub async fn upsert_room_member(&self, room_id: &str, member: &RoomMember) -> Result<()> {
+        room::upsert_member(&**self.client().await?, room_id, member).await
+    }
+
+    pub async fn select_room_members(&self, room_id: &str) -> Result<Vec<RoomMember>> {
+        room::select_members(&**self.client().await?, room_id).await
+    }
+
+    // Rankings
+    pub async fn refresh_ranking(
+        &self,
+        span: RankingSpan,
+        since: Option<NaiveDate>,
+        today: NaiveDate,
+        level_size: i32,
+        line_masks: &[i32],
+    ) -> Result<u64> {
+        ranking::refresh(
+            &mut **self.client().await?,
+            span,
+            since,
+            today,
+            level_size,
+            line_masks,
+        )
+        .await
+    }
+
+    pub async fn select_ranking_summary(
+        &self,
+        span: RankingSpan,
+    ) -> Result<(i64, Option<DateTime<Utc>>)> {
+        ranking::select_summary(&**self.client().await?, span).await
+    }
+
+    pub async fn select_ranking_entries(
+        &self,
+        span: RankingSpan,
+        sort: RankingSort,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<RankingEntry>> {
+        ranking::select_entries(&**self.client().await?, span, sort, limit, offset).await
+    }
+
+    // Stats
+    pub async fn select_level_stats(
+        &self,
+        user_id: &str,
+        timezone: &str,
+        level_size: i32,
+        line_masks: &[i32],
+        bucket_width: i32,
+    ) -> Result<Vec<LevelStats>> {
+        stats::select_levels(
+            &**self.client().await?,
+            user_id,
+            timezone,
+            level_size,
+            line_masks,
+            bucket_width,
+        )
+        .await
+    }
+
+    // Streaks
+    pub async fn select_user_streaks(&self, user_id: &str) -> Result<Vec<UserStreak>> {
+        streak::select_by_user_id(&**self.client().await?, user_id).await
     }
 
-    pub async fn select_newest_chosen_date_of_problems(&self) -> Result<Option<NaiveDate>> {
-        problem::select_newest_chosen_date(&self.client).await
+    pub async fn upsert_user_streak(&self, user_id: &str, streak: &UserStreak) -> Result<()> {
+        streak::upsert(&**self.client().await?, user_id, streak).await
+    }
+
+    pub async fn replace_user_streaks(&self, user_id: &str, streaks: &[UserStreak]) -> Result<()> {
+        streak::replace_by_user_id(&mut **self.client().await?, user_id, streaks).await
     }
 
     // User status
+    pub async fn select_users_with_global_ac(&self) -> Result<Vec<String>> {
+        user_status::select_users_with_global_ac(&**self.client().await?).await
+    }
+
     pub async fn select_user_status(
         &self,
         user_id: &str,
         problem_row_id: i32,
     ) -> Result<Option<UserStatus>> {
-        user_status::select(&self.client, user_id, problem_row_id).await
+        user_status::select(&**self.client().await?, user_id, problem_row_id).await
     }
 
     pub async fn select_user_status_between_problem_row_id(
@@ -59,43 +282,146 @@ impl DatabaseClient {
         problem_row_id_to: i32,
     ) -> Result<Vec<UserStatus>> {
         user_status::select_between_problem_row_id(
-            &self.client,
+            &**self.client().await?,
             problem_row_id_from,
             problem_row_id_to,
         )
         .await
     }
 
-    pub async fn insert_user_status(&self, user_status: &UserStatus) -> Result<()> {
-        user_status::insert(&self.client, user_status).await
+    pub async fn select_user_status_by_problem_row_ids(
+        &self,
+        problem_row_ids: &[i32],
+    ) -> Result<Vec<UserStatus>> {
+        user_status::select_by_problem_row_ids(&**self.client().await?, problem_row_ids).await
+    }
+
+    pub async fn select_user_status_by_user_id_and_problem_row_ids(
+        &self,
+        user_id: &str,
+        problem_row_ids: &[i32],
+    ) -> Result<Vec<UserStatus>> {
+        user_status::select_by_user_id_and_problem_row_ids(
+            &**self.client().await?,
+            user_id,
+            problem_row_ids,
+        )
+        .await
+    }
+
+    pub async fn select_user_status_by_room_id(&self, room_id: &str) -> Result<Vec<UserStatus>> {
+        user_status::select_by_room_id(&**self.client().await?, room_id).await
+    }
+
+    pub async fn insert_user_status(&self, user_status: &UserStatus) -> Result<bool> {
+        user_status::insert(&**self.client().await?, user_status).await
     }
 
     pub async fn update_user_status(&self, user_status: &UserStatus) -> Result<()> {
-        user_status::update(&self.client, user_status).await
+        user_status::update(&**self.client().await?, user_status).await
+    }
+
+    // Webhooks
+    pub async fn insert_webhook(
+        &self,
+        url: &str,
+        format: WebhookFormat,
+        secret: &str,
+        room_id: Option<&str>,
+    ) -> Result<i32> {
+        webhook::insert(&**self.client().await?, url, format, secret, room_id).await
+    }
+
+    pub async fn select_webhooks(&self) -> Result<Vec<Webhook>> {
+        webhook::select_all(&**self.client().await?).await
+    }
+
+    pub async fn delete_webhook(&self, id: i32) -> Result<bool> {
+        webhook::delete(&**self.client().await?, id).await
+    }
+
+    pub async fn enqueue_webhook_deliveries(
+        &self,
+        room_id: Option<&str>,
+        payload: &serde_json::Value,
+    ) -> Result<u64> {
+        webhook::enqueue(&**self.client().await?, room_id, payload).await
+    }
+
+    pub async fn take_due_webhook_deliveries(
+        &self,
+        limit: i64,
+        lease_secs: f64,
+    ) -> Result<Vec<WebhookDelivery>> {
+        webhook::take_due(&**self.client().await?, limit, lease_secs).await
+    }
+
+    pub async fn mark_webhook_delivered(&self, id: i32) -> Result<()> {
+        webhook::mark_delivered(&**self.client().await?, id).await
+    }
+
+    pub async fn mark_webhook_failed(
+        &self,
+        id: i32,
+        error: &str,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        webhook::mark_failed(&**self.client().await?, id, error, next_attempt_at).await
+    }
+}
+
+/// Listen to `channel` on a dedicated connection, and call `on_payload` with each notification.
+/// Return only when the connection fails.
+pub async fn listen(channel: &str, mut on_payload: impl FnMut(&str)) -> Result<()> {
+    let url = env::var("POSTGRES_URL").expect("POSTGRES_URL is not set.");
+    let (client, mut connection) = tokio_postgres::connect(&url, NoTls).await?;
+    let mut messages = stream::poll_fn(move |cx| connection.poll_message(cx));
+
+    // The connection must be polled while `LISTEN` is executed.
+    let query = format!("LISTEN {channel}");
+    let listen = client.batch_execute(&query);
+    tokio::pin!(listen);
+    let mut listening = false;
+    loop {
+        tokio::select! {
+            result = &mut listen, if !listening => {
+                result?;
+                listening = true;
+                log::info!("Start to listen to {channel}.");
+            }
+            message = messages.next() => match message {
+                Some(Ok(AsyncMessage::Notification(notification))) => {
+                    on_payload(notification.payload())
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+                None => bail!("The connection to listen to {channel} is closed."),
+            }
+        }
     }
 }
 
 /// Try to connect to the database until success.
-/// Then return the client.
-async fn get_connection() -> Client {
+/// Then return the connection pool.
+async fn get_pool() -> Pool {
     let url = env::var("POSTGRES_URL").expect("POSTGRES_URL is not set.");
+    let pg_config: tokio_postgres::Config = url.parse().expect("POSTGRES_URL is invalid.");
+
+    let manager_config = ManagerConfig {
+        recycling_method: RecyclingMethod::Fast,
+    };
+    let manager = Manager::from_config(pg_config, NoTls, manager_config);
+    let pool = Pool::builder(manager)
+        .max_size(16)
+        .build()
+        .expect("Failed to build the connection pool.");
 
-    let mut conn = tokio_postgres::connect(&url, NoTls).await;
-    while let Err(e) = conn {
+    while let Err(e) = pool.get().await {
         log::error!("Failed to connect to the database: {e}");
         sleep(Duration::from_secs(5)).await;
         log::error!("Try to connect again...");
-        conn = tokio_postgres::connect(&url, NoTls).await;
     }
-
-    let (client, connection) = conn.unwrap();
     log::info!("Succeed to connect to the database.");
 
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            log::error!("Connection error: {}", e);
-        }
-    });
-
-    client
+    pool
 }
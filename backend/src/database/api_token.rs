@@ -0,0 +1,49 @@
+use super::models::ApiToken;
+use crate::auth::Scope;
+use anyhow::Result;
+use tokio_postgres::Client;
+
+/// Return the ID of the inserted token.
+pub async fn insert(
+    client: &Client,
+    name: &str,
+    token_hash: &str,
+    scopes: &[Scope],
+) -> Result<i32> {
+    let row = client
+        .query_one(
+            "INSERT INTO api_tokens (name, token_hash, scopes) VALUES ($1, $2, $3) RETURNING id",
+            &[&name, &token_hash, &scopes],
+        )
+        .await?;
+    Ok(row.get("id"))
+}
+
+/// The token whose hash is `token_hash` unless it is revoked.
+pub async fn select_active_by_hash(client: &Client, token_hash: &str) -> Result<Option<ApiToken>> {
+    let row = client
+        .query_opt(
+            "SELECT * FROM api_tokens WHERE token_hash = $1 AND revoked_at IS NULL",
+            &[&token_hash],
+        )
+        .await?;
+    Ok(row.map(ApiToken::from))
+}
+
+pub async fn select_all(client: &Client) -> Result<Vec<ApiToken>> {
+    let rows = client
+        .query("SELECT * FROM api_tokens ORDER BY id", &[])
+        .await?;
+    Ok(rows.into_iter().map(ApiToken::from).collect())
+}
+
+/// Return whether or not an active token is revoked.
+pub async fn revoke(client: &Client, id: i32) -> Result<bool> {
+    let revoked = client
+        .execute(
+            "UPDATE api_tokens SET revoked_at = now() WHERE id = $1 AND revoked_at IS NULL",
+            &[&id],
+        )
+        .await?;
+    Ok(revoked == 1)
+}
@@ -0,0 +1,27 @@
+use super::models::{AuditEntry, NewAuditEntry};
+use anyhow::Result;
+use tokio_postgres::{Client, GenericClient};
+
+/// Record `entry`, in the transaction of the change if `client` is a transaction.
+pub async fn insert(client: &impl GenericClient, entry: &NewAuditEntry) -> Result<()> {
+    client
+        .execute(
+            "INSERT INTO admin_audit_log (actor, action, detail) VALUES ($1, $2, $3)",
+            &[&entry.actor, &entry.action, &entry.detail],
+        )
+        .await?;
+    Ok(())
+}
+
+/// The newest `limit` entries, newest first.
+pub async fn select_newest(client: &Client, limit: i64) -> Result<Vec<AuditEntry>> {
+    let rows = client
+        .query(
+            "SELECT * FROM admin_audit_log ORDER BY id DESC LIMIT $1",
+            &[&limit],
+        )
+        .await?;
+
+    let entries = rows.into_iter().map(AuditEntry::from).collect();
+    Ok(entries)
+}
@@ -0,0 +1,60 @@
+use anyhow::Result;
+use deadpool_postgres::Object;
+
+/// Migrations in the order of their versions.
+const MIGRATIONS: [(i32, &str); 10] = [
+    (1, include_str!("../../migrations/0001_create_tables.sql")),
+    (2, include_str!("../../migrations/0002_rooms.sql")),
+    (3, include_str!("../../migrations/0003_board_cadences.sql")),
+    (
+        4,
+        include_str!("../../migrations/0004_user_status_index.sql"),
+    ),
+    (5, include_str!("../../migrations/0005_admin_audit_log.sql")),
+    (6, include_str!("../../migrations/0006_api_tokens.sql")),
+    (7, include_str!("../../migrations/0007_webhooks.sql")),
+    (8, include_str!("../../migrations/0008_user_streaks.sql")),
+    (9, include_str!("../../migrations/0009_rankings.sql")),
+    (
+        10,
+        include_str!("../../migrations/0010_unique_global_cells.sql"),
+    ),
+];
+
+/// Apply migrations newer than the current version, each in a transaction.
+/// Return the versions of the applied migrations.
+pub async fn migrate(client: &mut Object) -> Result<Vec<i32>> {
+    client
+        .batch_execute(
+            "CREATE TABLE IF NOT EXISTS schema_migrations (\
+            version INT PRIMARY KEY, \
+            applied_at TIMESTAMPTZ NOT NULL DEFAULT now())",
+        )
+        .await?;
+
+    let row = client
+        .query_one("SELECT max(version) FROM schema_migrations", &[])
+        .await?;
+    let current_version: i32 = row.get::<_, Option<i32>>("max").unwrap_or(0);
+
+    let mut applied_versions = Vec::new();
+    for (version, sql) in MIGRATIONS {
+        if version <= current_version {
+            continue;
+        }
+
+        let transaction = client.transaction().await?;
+        transaction.batch_execute(sql).await?;
+        transaction
+            .execute(
+                "INSERT INTO schema_migrations (version) VALUES ($1)",
+                &[&version],
+            )
+            .await?;
+        transaction.commit().await?;
+
+        log::info!("Migration {version} is applied.");
+        applied_versions.push(version);
+    }
+    Ok(applied_versions)
+}
@@ -1,16 +1,39 @@
-use chrono::NaiveDate;
-use serde::Serialize;
+use crate::{
+    auth::Scope,
+    bingo::{Band, Cadence},
+};
+use anyhow::{bail, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use postgres_types::{FromSql, Json, ToSql};
+use serde::{Deserialize, Serialize};
 
 /// Problem information with its estimated difficulty.
 #[derive(Clone, Debug, Serialize)]
 pub struct Problem {
     pub id: i32,
+    /// The first day of the period of the board.
     pub chosen_date: NaiveDate,
+    pub cadence: Cadence,
+    /// The last day of the period of the board (inclusive).
+    pub period_end: NaiveDate,
     pub position: i32,
     pub problem_id: String,
     pub contest_id: String,
     pub title: String,
     pub difficulty: i32,
+    /// `None` for the global bingo.
+    pub room_id: Option<String>,
+}
+
+/// Page of the problem on AtCoder.
+pub fn task_url(contest_id: &str, problem_id: &str) -> String {
+    format!("https://atcoder.jp/contests/{contest_id}/tasks/{problem_id}")
+}
+
+impl Problem {
+    pub fn url(&self) -> String {
+        task_url(&self.contest_id, &self.problem_id)
+    }
 }
 
 impl From<tokio_postgres::Row> for Problem {
@@ -18,11 +41,14 @@ impl From<tokio_postgres::Row> for Problem {
         Problem {
             id: row.get("id"),
             chosen_date: row.get("chosen_date"),
+            cadence: row.get("cadence"),
+            period_end: row.get("period_end"),
             position: row.get("position"),
             problem_id: row.get("problem_id"),
             contest_id: row.get("contest_id"),
             title: row.get("title"),
             difficulty: row.get("difficulty"),
+            room_id: row.get("room_id"),
         }
     }
 }
@@ -33,6 +59,25 @@ pub struct UserStatus {
     pub user_id: String,
     pub problem_row_id: i32,
     pub accepted: bool,
+    /// Time of the first AC submission.
+    pub accepted_time: Option<DateTime<Utc>>,
+    /// ID of the first AC submission.
+    pub submission_id: Option<i64>,
+}
+
+impl UserStatus {
+    /// Whether this status was accepted earlier than `other`.
+    /// Ties of the submission time are broken by the submission ID.
+    pub fn accepted_before(&self, other: &UserStatus) -> bool {
+        match (self.accepted, other.accepted) {
+            (true, true) => {
+                (self.accepted_time, self.submission_id)
+                    < (other.accepted_time, other.submission_id)
+            }
+            (true, false) => true,
+            _ => false,
+        }
+    }
 }
 
 impl From<tokio_postgres::Row> for UserStatus {
@@ -41,6 +86,313 @@ impl From<tokio_postgres::Row> for UserStatus {
             user_id: row.get("user_id"),
             problem_row_id: row.get("problem_row_id"),
             accepted: row.get("accepted"),
+            accepted_time: row.get("accepted_time"),
+            submission_id: row.get("submission_id"),
+        }
+    }
+}
+
+/// How the cells of a room are filled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSql, FromSql)]
+#[serde(rename_all = "snake_case")]
+#[postgres(name = "room_mode")]
+pub enum RoomMode {
+    /// Every member fills their own board.
+    #[postgres(name = "individual")]
+    Individual,
+    /// An AC by any member fills the cell for their team.
+    /// Every team can fill every cell.
+    #[postgres(name = "team_shared")]
+    TeamShared,
+    /// The first team to AC a problem owns the cell.
+    #[postgres(name = "team_claim_first")]
+    TeamClaimFirst,
+    /// The first member to AC a problem owns the cell.
+    /// Other members can no longer fill it.
+    #[postgres(name = "lockout")]
+    Lockout,
+}
+
+impl RoomMode {
+    /// Whether or not members are split into teams.
+    pub fn is_team_mode(self) -> bool {
+        match self {
+            RoomMode::Individual | RoomMode::Lockout => false,
+            RoomMode::TeamShared | RoomMode::TeamClaimFirst => true,
+        }
+    }
+
+    /// Whether or not only the first AC fills each cell.
+    pub fn is_claim_first(self) -> bool {
+        match self {
+            RoomMode::Individual | RoomMode::TeamShared => false,
+            RoomMode::TeamClaimFirst | RoomMode::Lockout => true,
+        }
+    }
+
+    /// Fail unless a member can play in `team` in a room of this mode with `teams`.
+    pub fn validate_team(self, team: Option<&str>, teams: &[&str]) -> Result<()> {
+        match (self.is_team_mode(), team) {
+            (true, Some(team)) if teams.contains(&team) => Ok(()),
+            (true, Some(team)) => bail!("Team {team:?} is not in the room."),
+            (true, None) => bail!("A team must be specified in this room."),
+            (false, Some(_)) => bail!("Teams are not played in this room."),
+            (false, None) => Ok(()),
+        }
+    }
+}
+
+/// Private bingo with its own board and members.
+#[derive(Clone, Debug, Serialize)]
+pub struct Room {
+    pub id: String,
+    pub name: String,
+    pub mode: RoomMode,
+    pub bands: Vec<Band>,
+    /// Only submissions in [start_time, end_time) are counted.
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+}
+
+impl From<tokio_postgres::Row> for Room {
+    fn from(row: tokio_postgres::Row) -> Self {
+        let bands: Json<Vec<Band>> = row.get("bands");
+        Self {
+            id: row.get("id"),
+            name: row.get("name"),
+            mode: row.get("mode"),
+            bands: bands.0,
+            start_time: row.get("start_time"),
+            end_time: row.get("end_time"),
+        }
+    }
+}
+
+/// Member of a room.
+#[derive(Clone, Debug, Serialize)]
+pub struct RoomMember {
+    pub user_id: String,
+    /// `None` unless the room is played by teams.
+    pub team: Option<String>,
+}
+
+impl From<tokio_postgres::Row> for RoomMember {
+    fn from(row: tokio_postgres::Row) -> Self {
+        Self {
+            user_id: row.get("user_id"),
+            team: row.get("team"),
+        }
+    }
+}
+
+/// Action taken through the admin API.
+#[derive(Clone, Debug, Serialize)]
+pub struct AuditEntry {
+    pub id: i32,
+    /// Name of the token which is used for the action.
+    pub actor: String,
+    pub action: String,
+    /// Parameters of the action.
+    pub detail: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<tokio_postgres::Row> for AuditEntry {
+    fn from(row: tokio_postgres::Row) -> Self {
+        Self {
+            id: row.get("id"),
+            actor: row.get("actor"),
+            action: row.get("action"),
+            detail: row.get("detail"),
+            created_at: row.get("created_at"),
+        }
+    }
+}
+
+/// Action to be recorded in the audit log.
+#[derive(Clone, Debug)]
+pub struct NewAuditEntry {
+    pub actor: String,
+    pub action: String,
+    pub detail: serde_json::Value,
+}
+
+/// Token stored in the database.
+/// Only the hash of the token is stored.
+#[derive(Clone, Debug, Serialize)]
+pub struct ApiToken {
+    pub id: i32,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub created_at: DateTime<Utc>,
+    /// `None` while the token is active.
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<tokio_postgres::Row> for ApiToken {
+    fn from(row: tokio_postgres::Row) -> Self {
+        Self {
+            id: row.get("id"),
+            name: row.get("name"),
+            scopes: row.get("scopes"),
+            created_at: row.get("created_at"),
+            revoked_at: row.get("revoked_at"),
+        }
+    }
+}
+
+/// Format of the payloads sent to a webhook.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSql, FromSql)]
+#[serde(rename_all = "snake_case")]
+#[postgres(name = "webhook_format")]
+pub enum WebhookFormat {
+    /// The notification itself.
+    #[postgres(name = "json")]
+    Json,
+    /// A message for an incoming webhook of Discord.
+    #[postgres(name = "discord")]
+    Discord,
+    /// A message for an incoming webhook of Slack.
+    #[postgres(name = "slack")]
+    Slack,
+}
+
+/// Endpoint notified of new boards and bingos.
+#[derive(Clone, Debug, Serialize)]
+pub struct Webhook {
+    pub id: i32,
+    pub url: String,
+    pub format: WebhookFormat,
+    /// Key to sign the payloads, which is shown only when the webhook is created.
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// `None` for the notifications of the global boards.
+    pub room_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<tokio_postgres::Row> for Webhook {
+    fn from(row: tokio_postgres::Row) -> Self {
+        Self {
+            id: row.get("id"),
+            url: row.get("url"),
+            format: row.get("format"),
+            secret: row.get("secret"),
+            room_id: row.get("room_id"),
+            created_at: row.get("created_at"),
+        }
+    }
+}
+
+/// Notification waiting to be sent to a webhook.
+#[derive(Clone, Debug)]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub webhook: Webhook,
+    pub payload: serde_json::Value,
+    /// The number of the failed attempts.
+    pub attempts: i32,
+}
+
+impl From<tokio_postgres::Row> for WebhookDelivery {
+    fn from(row: tokio_postgres::Row) -> Self {
+        Self {
+            id: row.get("delivery_id"),
+            payload: row.get("payload"),
+            attempts: row.get("attempts"),
+            webhook: Webhook::from(row),
+        }
+    }
+}
+
+/// Streak of a user stored in the database.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UserStreak {
+    pub kind: String,
+    pub current: i32,
+    pub longest: i32,
+    /// The last day counted in the current streak.
+    pub last_date: NaiveDate,
+}
+
+impl From<tokio_postgres::Row> for UserStreak {
+    fn from(row: tokio_postgres::Row) -> Self {
+        Self {
+            kind: row.get("kind"),
+            current: row.get("current"),
+            longest: row.get("longest"),
+            last_date: row.get("last_date"),
+        }
+    }
+}
+
+/// Totals of a user in a level of the global boards.
+#[derive(Clone, Debug, Serialize)]
+pub struct LevelStats {
+    /// 0-indexed.
+    pub level: i32,
+    /// The number of solved cells.
+    pub cells: i64,
+    /// The number of completed lines.
+    pub bingos: i64,
+    /// The number of boards on which all cells of the level are filled.
+    pub full_boards: i64,
+    /// Average time from the start of the board to the first AC.
+    pub average_accepted_seconds: Option<f64>,
+    /// Solved cells by difficulty, ordered from the easiest.
+    pub difficulty_histogram: Vec<DifficultyBucket>,
+}
+
+impl From<tokio_postgres::Row> for LevelStats {
+    fn from(row: tokio_postgres::Row) -> Self {
+        Self {
+            level: row.get("level"),
+            cells: row.get("cells"),
+            bingos: row.get("bingos"),
+            full_boards: row.get("full_boards"),
+            average_accepted_seconds: row.get("average_accepted_seconds"),
+            difficulty_histogram: Vec::new(),
+        }
+    }
+}
+
+/// Solved cells whose difficulty is in [lower, lower + width of the buckets).
+#[derive(Clone, Debug, Serialize)]
+pub struct DifficultyBucket {
+    pub lower: i32,
+    pub cells: i64,
+}
+
+impl From<tokio_postgres::Row> for DifficultyBucket {
+    fn from(row: tokio_postgres::Row) -> Self {
+        Self {
+            lower: row.get("lower"),
+            cells: row.get("cells"),
+        }
+    }
+}
+
+/// A user in a ranking.
+#[derive(Clone, Debug, Serialize)]
+pub struct RankingEntry {
+    /// 1-indexed. Users with the same value have the same rank.
+    pub rank: i64,
+    pub user_id: String,
+    pub bingos: i64,
+    pub full_boards: i64,
+    pub streak: i32,
+    pub score: i64,
+}
+
+impl From<tokio_postgres::Row> for RankingEntry {
+    fn from(row: tokio_postgres::Row) -> Self {
+        Self {
+            rank: row.get("rank"),
+            user_id: row.get("user_id"),
+            bingos: row.get("bingos"),
+            full_boards: row.get("full_boards"),
+            streak: row.get("streak"),
+            score: row.get("score"),
         }
     }
 }
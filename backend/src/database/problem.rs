@@ -1,35 +1,237 @@
-use super::models::Problem;
+use super::{
+    audit,
+    models::{NewAuditEntry, Problem},
+    streak,
+};
+use crate::bingo::{BoardPeriod, Cadence};
 use anyhow::Result;
-use chrono::NaiveDate;
-use tokio_postgres::Client;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::BTreeSet;
+use tokio_postgres::{error::SqlState, Client, GenericClient, Row, Transaction};
 
-pub async fn insert(client: &Client, problem: &Problem) -> Result<()> {
-    client
+pub(super) async fn insert(transaction: &Transaction<'_>, problem: &Problem) -> Result<()> {
+    transaction
         .execute(
             "INSERT INTO problems \
-            (chosen_date, position, problem_id, contest_id, title, difficulty) \
-            VALUES ($1, $2, $3, $4, $5, $6)",
+            (chosen_date, cadence, period_end, position, \
+            problem_id, contest_id, title, difficulty, room_id) \
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
             &[
                 &problem.chosen_date,
+                &problem.cadence,
+                &problem.period_end,
                 &problem.position,
                 &problem.problem_id,
                 &problem.contest_id,
                 &problem.title,
                 &problem.difficulty,
+                &problem.room_id,
             ],
         )
         .await?;
     Ok(())
 }
 
-pub async fn select_by_chosen_date(
+/// Recalculate the streaks of the users in `rows` of the deleted user status
+/// from their remaining solves, if the status was for a daily global board.
+async fn rebuild_streaks(
+    transaction: &Transaction<'_>,
+    cadence: Cadence,
+    rows: &[Row],
+) -> Result<()> {
+    if cadence != Cadence::Daily {
+        return Ok(());
+    }
+    let user_ids: BTreeSet<String> = rows.iter().map(|row| row.get("user_id")).collect();
+    for user_id in &user_ids {
+        let solved = select_solved_global(transaction, user_id).await?;
+        streak::replace(transaction, user_id, &crate::streak::from_solved(&solved)).await?;
+    }
+    Ok(())
+}
+
+/// Delete the global board for `period` with the user status for it,
+/// and rebuild the streaks which counted the board.
+/// Return the number of the deleted problems.
+async fn delete_period(transaction: &Transaction<'_>, period: &BoardPeriod) -> Result<u64> {
+    let deleted_status = transaction
+        .query(
+            "DELETE FROM user_status USING problems \
+            WHERE user_status.problem_row_id = problems.id \
+            AND problems.cadence = $1 AND problems.chosen_date = $2 \
+            AND problems.room_id IS NULL \
+            RETURNING user_status.user_id",
+            &[&period.cadence, &period.start],
+        )
+        .await?;
+    let deleted = transaction
+        .execute(
+            "DELETE FROM problems \
+            WHERE cadence = $1 AND chosen_date = $2 AND room_id IS NULL",
+            &[&period.cadence, &period.start],
+        )
+        .await?;
+    rebuild_streaks(transaction, period.cadence, &deleted_status).await?;
+    Ok(deleted)
+}
+
+/// Insert `problems` in a transaction so that a board is never left half-written.
+/// Return `false` without changes if a cell of the global board already exists,
+/// e.g. when another process has just stored the board.
+pub async fn insert_all(client: &mut Client, problems: &[Problem]) -> Result<bool> {
+    let transaction = client.transaction().await?;
+    for problem in problems {
+        if let Err(e) = insert(&transaction, problem).await {
+            let code = e
+                .downcast_ref::<tokio_postgres::Error>()
+                .and_then(|e| e.code());
+            if code == Some(&SqlState::UNIQUE_VIOLATION) {
+                return Ok(false);
+            }
+            return Err(e);
+        }
+    }
+    transaction.commit().await?;
+    Ok(true)
+}
+
+/// Replace the global board for `period` with `problems` in a transaction with `audit`.
+/// The user status for the old board is deleted, and the streaks are rebuilt without it.
+pub async fn replace_period(
+    client: &mut Client,
+    period: &BoardPeriod,
+    problems: &[Problem],
+    audit: &NewAuditEntry,
+) -> Result<()> {
+    let transaction = client.transaction().await?;
+    delete_period(&transaction, period).await?;
+    for problem in problems {
+        insert(&transaction, problem).await?;
+    }
+    audit::insert(&transaction, audit).await?;
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Delete the global board for `period` with the user status for it,
+/// and rebuild the streaks and record `audit` in the same transaction if the board exists.
+/// Return the number of the deleted problems.
+pub async fn delete_by_period(
+    client: &mut Client,
+    period: &BoardPeriod,
+    audit: &NewAuditEntry,
+) -> Result<u64> {
+    let transaction = client.transaction().await?;
+    let deleted = delete_period(&transaction, period).await?;
+    if deleted == 0 {
+        return Ok(0);
+    }
+    audit::insert(&transaction, audit).await?;
+    transaction.commit().await?;
+    Ok(deleted)
+}
+
+/// Replace the problem in the row of `problem.id` in a transaction with `audit`.
+/// The user status for the old problem is deleted, and the streaks are rebuilt without it.
+pub async fn update(client: &mut Client, problem: &Problem, audit: &NewAuditEntry) -> Result<()> {
+    let transaction = client.transaction().await?;
+    let deleted_status = transaction
+        .query(
+            "DELETE FROM user_status WHERE problem_row_id = $1 RETURNING user_id",
+            &[&problem.id],
+        )
+        .await?;
+    transaction
+        .execute(
+            "UPDATE problems \
+            SET problem_id = $1, contest_id = $2, title = $3, difficulty = $4 \
+            WHERE id = $5",
+            &[
+                &problem.problem_id,
+                &problem.contest_id,
+                &problem.title,
+                &problem.difficulty,
+                &problem.id,
+            ],
+        )
+        .await?;
+    if problem.room_id.is_none() {
+        rebuild_streaks(&transaction, problem.cadence, &deleted_status).await?;
+    }
+    audit::insert(&transaction, audit).await?;
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Problems of the global board for `period`.
+pub async fn select_by_period(client: &Client, period: &BoardPeriod) -> Result<Vec<Problem>> {
+    let rows = client
+        .query(
+            "SELECT * FROM problems \
+            WHERE cadence = $1 AND chosen_date = $2 AND room_id IS NULL \
+            ORDER BY position asc",
+            &[&period.cadence, &period.start],
+        )
+        .await?;
+
+    let problems: Vec<Problem> = rows.into_iter().map(Problem::from).collect();
+
+    Ok(problems)
+}
+
+/// Problems of the newest `days` global daily boards until `until`,
+/// ordered from the newest board.
+pub async fn select_recent_daily(
     client: &Client,
-    chosen_date: &NaiveDate,
+    until: &NaiveDate,
+    days: i64,
+) -> Result<Vec<Problem>> {
+    let rows = client
+        .query(
+            "SELECT * FROM problems \
+            WHERE cadence = 'daily' AND room_id IS NULL AND chosen_date IN ( \
+                SELECT DISTINCT chosen_date FROM problems \
+                WHERE cadence = 'daily' AND room_id IS NULL AND chosen_date <= $1 \
+                ORDER BY chosen_date desc LIMIT $2 \
+            ) \
+            ORDER BY chosen_date desc, position asc",
+            &[&until, &days],
+        )
+        .await?;
+
+    let problems: Vec<Problem> = rows.into_iter().map(Problem::from).collect();
+
+    Ok(problems)
+}
+
+/// Problems of the global boards whose period ends on or after `since`,
+/// ordered by the boards.
+pub async fn select_global_since(client: &Client, since: &NaiveDate) -> Result<Vec<Problem>> {
+    let rows = client
+        .query(
+            "SELECT * FROM problems WHERE room_id IS NULL AND period_end >= $1 \
+            ORDER BY chosen_date asc, cadence asc, position asc",
+            &[&since],
+        )
+        .await?;
+
+    let problems: Vec<Problem> = rows.into_iter().map(Problem::from).collect();
+
+    Ok(problems)
+}
+
+/// Problems of the global boards which `user_id` solved.
+pub async fn select_solved_global(
+    client: &impl GenericClient,
+    user_id: &str,
 ) -> Result<Vec<Problem>> {
     let rows = client
         .query(
-            "SELECT * FROM problems WHERE chosen_date = $1 ORDER BY position asc",
-            &[chosen_date],
+            "SELECT problems.* FROM problems \
+            JOIN user_status ON user_status.problem_row_id = problems.id \
+            WHERE user_status.user_id = $1 AND user_status.accepted \
+            AND problems.room_id IS NULL",
+            &[&user_id],
         )
         .await?;
 
@@ -38,27 +240,59 @@ pub async fn select_by_chosen_date(
     Ok(problems)
 }
 
-pub async fn select_by_chosen_date_and_id(
+/// Problems with `problem_id` in the global boards whose period contains `date`.
+pub async fn select_by_date_and_id(
     client: &Client,
-    chosen_date: &NaiveDate,
+    date: &NaiveDate,
     problem_id: &str,
-) -> Result<Option<Problem>> {
-    let row = client
-        .query_opt(
+) -> Result<Vec<Problem>> {
+    let rows = client
+        .query(
             "SELECT * FROM problems \
-            WHERE chosen_date = $1 AND problem_id = $2",
-            &[chosen_date, &problem_id],
+            WHERE chosen_date <= $1 AND $1 <= period_end \
+            AND problem_id = $2 AND room_id IS NULL",
+            &[date, &problem_id],
         )
         .await?;
 
-    let problem = row.map(Problem::from);
-    Ok(problem)
+    let problems: Vec<Problem> = rows.into_iter().map(Problem::from).collect();
+
+    Ok(problems)
 }
 
-pub async fn select_newest_chosen_date(client: &Client) -> Result<Option<NaiveDate>> {
-    let row = client
-        .query_one("SELECT max(chosen_date) FROM problems", &[])
+pub async fn select_by_room_id(client: &Client, room_id: &str) -> Result<Vec<Problem>> {
+    let rows = client
+        .query(
+            "SELECT * FROM problems WHERE room_id = $1 ORDER BY position asc",
+            &[&room_id],
+        )
         .await?;
-    let newest_chosen_date = row.try_get("max").ok();
-    Ok(newest_chosen_date)
+
+    let problems: Vec<Problem> = rows.into_iter().map(Problem::from).collect();
+
+    Ok(problems)
+}
+
+/// Problems with `problem_id` in the rooms
+/// which `user_id` is a member of and are open at `submission_time`.
+pub async fn select_in_open_rooms_of_user(
+    client: &Client,
+    user_id: &str,
+    problem_id: &str,
+    submission_time: &DateTime<Utc>,
+) -> Result<Vec<Problem>> {
+    let rows = client
+        .query(
+            "SELECT problems.* FROM problems \
+            JOIN rooms ON problems.room_id = rooms.id \
+            JOIN room_members ON rooms.id = room_members.room_id \
+            WHERE room_members.user_id = $1 AND problems.problem_id = $2 \
+            AND rooms.start_time <= $3 AND $3 < rooms.end_time",
+            &[&user_id, &problem_id, submission_time],
+        )
+        .await?;
+
+    let problems: Vec<Problem> = rows.into_iter().map(Problem::from).collect();
+
+    Ok(problems)
 }
@@ -0,0 +1,96 @@
+use super::{models::RankingEntry, stats::line_ctes};
+use crate::ranking::{RankingSort, RankingSpan};
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use tokio_postgres::Client;
+
+/// Recalculate the ranking for `span` in a transaction.
+/// Only the global boards from `since` are counted, or all of them if it is `None`.
+/// `line_masks` are the bit masks of the cells in each line of a level.
+/// Return the number of the ranked users.
+pub async fn refresh(
+    client: &mut Client,
+    span: RankingSpan,
+    since: Option<NaiveDate>,
+    today: NaiveDate,
+    level_size: i32,
+    line_masks: &[i32],
+) -> Result<u64> {
+    let full_mask = (1 << level_size) - 1;
+    let transaction = client.transaction().await?;
+    transaction
+        .execute("DELETE FROM rankings WHERE span = $1", &[&span])
+        .await?;
+    let ranked = transaction
+        .execute(
+            &format!(
+                "INSERT INTO rankings (span, user_id, bingos, full_boards, streak, score) \
+                WITH solved AS ( \
+                    SELECT user_status.user_id, problems.cadence, problems.chosen_date, \
+                    problems.position, problems.difficulty \
+                    FROM user_status JOIN problems ON user_status.problem_row_id = problems.id \
+                    WHERE user_status.accepted AND problems.room_id IS NULL \
+                    AND ($2::date IS NULL OR problems.chosen_date >= $2) \
+                ), \
+                {}, \
+                scores AS ( \
+                    SELECT user_id, sum(greatest(difficulty, 0))::int8 AS score \
+                    FROM solved GROUP BY user_id \
+                ), \
+                streaks AS ( \
+                    SELECT user_id, CASE \
+                        WHEN $2::date IS NULL THEN longest \
+                        WHEN last_date >= $6::date - 1 THEN current \
+                        ELSE 0 END AS streak \
+                    FROM user_streaks WHERE kind = 'bingo' \
+                ) \
+                SELECT $1, user_id, lines.bingos, lines.full_boards, \
+                coalesce(streaks.streak, 0), scores.score \
+                FROM lines JOIN scores USING (user_id) LEFT JOIN streaks USING (user_id)",
+                line_ctes("user_id", "user_id")
+            ),
+            &[&span, &since, &level_size, &line_masks, &full_mask, &today],
+        )
+        .await?;
+    transaction.commit().await?;
+    Ok(ranked)
+}
+
+/// The number of the users in the ranking for `span`, and when it was refreshed.
+pub async fn select_summary(
+    client: &Client,
+    span: RankingSpan,
+) -> Result<(i64, Option<DateTime<Utc>>)> {
+    let row = client
+        .query_one(
+            "SELECT count(*) AS total, max(refreshed_at) AS refreshed_at \
+            FROM rankings WHERE span = $1",
+            &[&span],
+        )
+        .await?;
+    Ok((row.get("total"), row.get("refreshed_at")))
+}
+
+/// Entries of the ranking for `span` ordered by `sort`.
+/// Ties share the same rank, and are ordered by the user ID.
+pub async fn select_entries(
+    client: &Client,
+    span: RankingSpan,
+    sort: RankingSort,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<RankingEntry>> {
+    let column = sort.column();
+    let rows = client
+        .query(
+            &format!(
+                "SELECT rank() OVER (ORDER BY {column} DESC) AS rank, \
+                user_id, bingos, full_boards, streak, score \
+                FROM rankings WHERE span = $1 \
+                ORDER BY {column} DESC, user_id LIMIT $2 OFFSET $3"
+            ),
+            &[&span, &limit, &offset],
+        )
+        .await?;
+    Ok(rows.into_iter().map(RankingEntry::from).collect())
+}
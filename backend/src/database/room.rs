@@ -0,0 +1,89 @@
+use super::{
+    models::{Problem, Room, RoomMember},
+    problem,
+};
+use anyhow::Result;
+use postgres_types::Json;
+use tokio_postgres::{Client, GenericClient};
+
+/// Insert `room` with its members and problems in a transaction
+/// so that a room is never left without them.
+pub async fn insert(
+    client: &mut Client,
+    room: &Room,
+    members: &[RoomMember],
+    problems: &[Problem],
+) -> Result<()> {
+    let transaction = client.transaction().await?;
+    transaction
+        .execute(
+            "INSERT INTO rooms (id, name, mode, bands, start_time, end_time) \
+            VALUES ($1, $2, $3, $4, $5, $6)",
+            &[
+                &room.id,
+                &room.name,
+                &room.mode,
+                &Json(&room.bands),
+                &room.start_time,
+                &room.end_time,
+            ],
+        )
+        .await?;
+    for member in members {
+        upsert_member(&transaction, &room.id, member).await?;
+    }
+    for problem in problems {
+        problem::insert(&transaction, problem).await?;
+    }
+    transaction.commit().await?;
+    Ok(())
+}
+
+pub async fn select(client: &Client, room_id: &str) -> Result<Option<Room>> {
+    let row = client
+        .query_opt("SELECT * FROM rooms WHERE id = $1", &[&room_id])
+        .await?;
+
+    let room = row.map(Room::from);
+    Ok(room)
+}
+
+/// Move the period in which submissions are counted.
+pub async fn update_window(client: &Client, room: &Room) -> Result<()> {
+    client
+        .execute(
+            "UPDATE rooms SET start_time = $2, end_time = $3 WHERE id = $1",
+            &[&room.id, &room.start_time, &room.end_time],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Add a member to the room.
+/// If the user is already a member, only the team is updated.
+pub async fn upsert_member(
+    client: &impl GenericClient,
+    room_id: &str,
+    member: &RoomMember,
+) -> Result<()> {
+    client
+        .execute(
+            "INSERT INTO room_members (room_id, user_id, team) VALUES ($1, $2, $3) \
+            ON CONFLICT (room_id, user_id) DO UPDATE SET team = EXCLUDED.team",
+            &[&room_id, &member.user_id, &member.team],
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn select_members(client: &Client, room_id: &str) -> Result<Vec<RoomMember>> {
+    let rows = client
+        .query(
+            "SELECT * FROM room_members WHERE room_id = $1 ORDER BY user_id asc",
+            &[&room_id],
+        )
+        .await?;
+
+    let members = rows.into_iter().map(RoomMember::from).collect();
+    Ok(members)
+}
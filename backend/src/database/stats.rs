@@ -0,0 +1,81 @@
+use super::models::{DifficultyBucket, LevelStats};
+use anyhow::Result;
+use tokio_postgres::Client;
+
+/// Accepted cells of the user on the global boards,
+/// with the time from the start of the board to the first AC.
+/// The parameters are the user ID and the timezone of the boards.
+const SOLVED_CELLS: &str = "\
+    SELECT problems.cadence, problems.chosen_date, problems.position, problems.difficulty, \
+    extract(epoch FROM user_status.accepted_time \
+        - (problems.chosen_date::timestamp AT TIME ZONE $2))::float8 AS elapsed_seconds \
+    FROM user_status JOIN problems ON user_status.problem_row_id = problems.id \
+    WHERE user_status.user_id = $1 AND user_status.accepted AND problems.room_id IS NULL";
+
+/// CTEs `levels` and `lines`, which count the completed lines and the full levels in `solved`
+/// for each `key` named `name`.
+/// `$3` is the number of cells in a level, `$4` the bit masks of the cells in each line of a level,
+/// and `$5` the bit mask of a full level.
+pub(super) fn line_ctes(key: &str, name: &str) -> String {
+    format!(
+        "levels AS ( \
+            SELECT {key} AS {name}, bit_or(1 << (position % $3)) AS mask \
+            FROM solved GROUP BY {key}, cadence, chosen_date, position / $3 \
+        ), \
+        lines AS ( \
+            SELECT {name}, \
+            sum((SELECT count(*) FROM unnest($4::int4[]) AS line \
+                WHERE mask & line = line))::int8 AS bingos, \
+            count(*) FILTER (WHERE mask = $5) AS full_boards \
+            FROM levels GROUP BY {name} \
+        )"
+    )
+}
+
+/// Totals of `user_id` in each level.
+/// `line_masks` are the bit masks of the cells in each line of a level.
+pub async fn select_levels(
+    client: &Client,
+    user_id: &str,
+    timezone: &str,
+    level_size: i32,
+    line_masks: &[i32],
+    bucket_width: i32,
+) -> Result<Vec<LevelStats>> {
+    let full_mask = (1 << level_size) - 1;
+    let rows = client
+        .query(
+            &format!(
+                "WITH solved AS ({SOLVED_CELLS}), {}, \
+                cells AS ( \
+                    SELECT position / $3 AS level, count(*) AS cells, \
+                    avg(elapsed_seconds) AS average_accepted_seconds \
+                    FROM solved GROUP BY position / $3 \
+                ) \
+                SELECT * FROM cells JOIN lines USING (level) ORDER BY level",
+                line_ctes("position / $3", "level")
+            ),
+            &[&user_id, &timezone, &level_size, &line_masks, &full_mask],
+        )
+        .await?;
+    let mut levels: Vec<LevelStats> = rows.into_iter().map(LevelStats::from).collect();
+
+    let rows = client
+        .query(
+            &format!(
+                "WITH solved AS ({SOLVED_CELLS}) \
+                SELECT position / $3 AS level, greatest(difficulty, 0) / $4 * $4 AS lower, \
+                count(*) AS cells \
+                FROM solved GROUP BY 1, 2 ORDER BY 1, 2"
+            ),
+            &[&user_id, &timezone, &level_size, &bucket_width],
+        )
+        .await?;
+    for row in rows {
+        let level: i32 = row.get("level");
+        if let Some(stats) = levels.iter_mut().find(|stats| stats.level == level) {
+            stats.difficulty_histogram.push(DifficultyBucket::from(row));
+        }
+    }
+    Ok(levels)
+}
@@ -0,0 +1,71 @@
+use super::models::UserStreak;
+use anyhow::Result;
+use tokio_postgres::{Client, GenericClient};
+
+pub async fn select_by_user_id(client: &Client, user_id: &str) -> Result<Vec<UserStreak>> {
+    let rows = client
+        .query(
+            "SELECT * FROM user_streaks WHERE user_id = $1 ORDER BY kind asc",
+            &[&user_id],
+        )
+        .await?;
+    Ok(rows.into_iter().map(UserStreak::from).collect())
+}
+
+pub async fn upsert(client: &Client, user_id: &str, streak: &UserStreak) -> Result<()> {
+    client
+        .execute(
+            "INSERT INTO user_streaks (user_id, kind, current, longest, last_date) \
+            VALUES ($1, $2, $3, $4, $5) \
+            ON CONFLICT (user_id, kind) DO UPDATE SET \
+            current = EXCLUDED.current, longest = EXCLUDED.longest, last_date = EXCLUDED.last_date",
+            &[
+                &user_id,
+                &streak.kind,
+                &streak.current,
+                &streak.longest,
+                &streak.last_date,
+            ],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Replace all streaks of `user_id` in a transaction.
+pub async fn replace_by_user_id(
+    client: &mut Client,
+    user_id: &str,
+    streaks: &[UserStreak],
+) -> Result<()> {
+    let transaction = client.transaction().await?;
+    replace(&transaction, user_id, streaks).await?;
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Replace all streaks of `user_id`, e.g. in the transaction which changes their solves.
+pub(super) async fn replace(
+    client: &impl GenericClient,
+    user_id: &str,
+    streaks: &[UserStreak],
+) -> Result<()> {
+    client
+        .execute("DELETE FROM user_streaks WHERE user_id = $1", &[&user_id])
+        .await?;
+    for streak in streaks {
+        client
+            .execute(
+                "INSERT INTO user_streaks (user_id, kind, current, longest, last_date) \
+                VALUES ($1, $2, $3, $4, $5)",
+                &[
+                    &user_id,
+                    &streak.kind,
+                    &streak.current,
+                    &streak.longest,
+                    &streak.last_date,
+                ],
+            )
+            .await?;
+    }
+    Ok(())
+}
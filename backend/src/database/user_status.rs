@@ -36,26 +36,98 @@ pub async fn select_between_problem_row_id(
     Ok(user_status)
 }
 
-pub async fn insert(client: &Client, user_status: &UserStatus) -> Result<()> {
-    client
+/// All user status for problems with one of `problem_row_ids`.
+pub async fn select_by_problem_row_ids(
+    client: &Client,
+    problem_row_ids: &[i32],
+) -> Result<Vec<UserStatus>> {
+    let row = client
+        .query(
+            "SELECT * FROM user_status WHERE problem_row_id = ANY($1)",
+            &[&problem_row_ids],
+        )
+        .await?;
+
+    let user_status = row.into_iter().map(UserStatus::from).collect();
+    Ok(user_status)
+}
+
+/// Status of `user_id` for problems with one of `problem_row_ids`.
+pub async fn select_by_user_id_and_problem_row_ids(
+    client: &Client,
+    user_id: &str,
+    problem_row_ids: &[i32],
+) -> Result<Vec<UserStatus>> {
+    let row = client
+        .query(
+            "SELECT * FROM user_status WHERE user_id = $1 AND problem_row_id = ANY($2)",
+            &[&user_id, &problem_row_ids],
+        )
+        .await?;
+
+    let user_status = row.into_iter().map(UserStatus::from).collect();
+    Ok(user_status)
+}
+
+/// All user status for problems of the room.
+pub async fn select_by_room_id(client: &Client, room_id: &str) -> Result<Vec<UserStatus>> {
+    let row = client
+        .query(
+            "SELECT user_status.* FROM user_status \
+            JOIN problems ON user_status.problem_row_id = problems.id \
+            WHERE problems.room_id = $1",
+            &[&room_id],
+        )
+        .await?;
+
+    let user_status = row.into_iter().map(UserStatus::from).collect();
+    Ok(user_status)
+}
+
+/// Users with an accepted cell on a global board.
+pub async fn select_users_with_global_ac(client: &Client) -> Result<Vec<String>> {
+    let rows = client
+        .query(
+            "SELECT DISTINCT user_status.user_id FROM user_status \
+            JOIN problems ON user_status.problem_row_id = problems.id \
+            WHERE user_status.accepted AND problems.room_id IS NULL \
+            ORDER BY user_status.user_id",
+            &[],
+        )
+        .await?;
+    Ok(rows.into_iter().map(|row| row.get("user_id")).collect())
+}
+
+/// Insert the user status unless it already exists.
+/// Return whether or not it is inserted.
+pub async fn insert(client: &Client, user_status: &UserStatus) -> Result<bool> {
+    let inserted = client
         .execute(
-            "INSERT INTO user_status (user_id, problem_row_id, accepted) VALUES ($1, $2, $3)",
+            "INSERT INTO user_status \
+            (user_id, problem_row_id, accepted, accepted_time, submission_id) \
+            VALUES ($1, $2, $3, $4, $5) \
+            ON CONFLICT (user_id, problem_row_id) DO NOTHING",
             &[
                 &user_status.user_id,
                 &user_status.problem_row_id,
                 &user_status.accepted,
+                &user_status.accepted_time,
+                &user_status.submission_id,
             ],
         )
         .await?;
-    Ok(())
+    Ok(inserted == 1)
 }
 
 pub async fn update(client: &Client, user_status: &UserStatus) -> Result<()> {
     client
         .execute(
-            "UPDATE user_status SET accepted = $1 WHERE user_id = $2 AND problem_row_id = $3",
+            "UPDATE user_status SET accepted = $1, accepted_time = $2, submission_id = $3 \
+            WHERE user_id = $4 AND problem_row_id = $5",
             &[
                 &user_status.accepted,
+                &user_status.accepted_time,
+                &user_status.submission_id,
                 &user_status.user_id,
                 &user_status.problem_row_id,
             ],
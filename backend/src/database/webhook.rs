@@ -0,0 +1,110 @@
+use super::models::{Webhook, WebhookDelivery, WebhookFormat};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use tokio_postgres::Client;
+
+/// Return the ID of the inserted webhook.
+pub async fn insert(
+    client: &Client,
+    url: &str,
+    format: WebhookFormat,
+    secret: &str,
+    room_id: Option<&str>,
+) -> Result<i32> {
+    let row = client
+        .query_one(
+            "INSERT INTO webhooks (url, format, secret, room_id) VALUES ($1, $2, $3, $4) \
+            RETURNING id",
+            &[&url, &format, &secret, &room_id],
+        )
+        .await?;
+    Ok(row.get("id"))
+}
+
+pub async fn select_all(client: &Client) -> Result<Vec<Webhook>> {
+    let rows = client
+        .query("SELECT * FROM webhooks ORDER BY id", &[])
+        .await?;
+    Ok(rows.into_iter().map(Webhook::from).collect())
+}
+
+/// Return whether or not the webhook existed.
+/// Its pending deliveries are also deleted.
+pub async fn delete(client: &Client, id: i32) -> Result<bool> {
+    let deleted = client
+        .execute("DELETE FROM webhooks WHERE id = $1", &[&id])
+        .await?;
+    Ok(deleted == 1)
+}
+
+/// Queue `payload` for the webhooks of the room, or of the deployment if `room_id` is `None`.
+/// Return the number of the queued deliveries.
+pub async fn enqueue(
+    client: &Client,
+    room_id: Option<&str>,
+    payload: &serde_json::Value,
+) -> Result<u64> {
+    let queued = client
+        .execute(
+            "INSERT INTO webhook_deliveries (webhook_id, payload) \
+            SELECT id, $2 FROM webhooks WHERE room_id IS NOT DISTINCT FROM $1",
+            &[&room_id, payload],
+        )
+        .await?;
+    Ok(queued)
+}
+
+/// Take at most `limit` deliveries whose attempt is due.
+/// They are put off by `lease_secs` seconds so that other workers do not take them meanwhile.
+pub async fn take_due(
+    client: &Client,
+    limit: i64,
+    lease_secs: f64,
+) -> Result<Vec<WebhookDelivery>> {
+    let rows = client
+        .query(
+            "WITH due AS ( \
+                SELECT id FROM webhook_deliveries WHERE next_attempt_at <= now() \
+                ORDER BY next_attempt_at LIMIT $1 FOR UPDATE SKIP LOCKED \
+            ) \
+            UPDATE webhook_deliveries AS d \
+            SET next_attempt_at = now() + $2 * interval '1 second' \
+            FROM due, webhooks AS w \
+            WHERE d.id = due.id AND w.id = d.webhook_id \
+            RETURNING d.id AS delivery_id, d.payload, d.attempts, w.*",
+            &[&limit, &lease_secs],
+        )
+        .await?;
+    Ok(rows.into_iter().map(WebhookDelivery::from).collect())
+}
+
+pub async fn mark_delivered(client: &Client, id: i32) -> Result<()> {
+    client
+        .execute(
+            "UPDATE webhook_deliveries \
+            SET attempts = attempts + 1, next_attempt_at = NULL, delivered_at = now() \
+            WHERE id = $1",
+            &[&id],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Record a failed attempt.
+/// The delivery is given up if `next_attempt_at` is `None`.
+pub async fn mark_failed(
+    client: &Client,
+    id: i32,
+    error: &str,
+    next_attempt_at: Option<DateTime<Utc>>,
+) -> Result<()> {
+    client
+        .execute(
+            "UPDATE webhook_deliveries \
+            SET attempts = attempts + 1, next_attempt_at = $2, last_error = $3 \
+            WHERE id = $1",
+            &[&id, &next_attempt_at, &error],
+        )
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use crate::{
+    bingo::Cadence,
+    database::{self, DatabaseClient},
+};
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+/// Channel of `NOTIFY` through which the events are delivered.
+const CHANNEL: &str = "bingo_events";
+
+/// Events kept for slow subscribers.
+const CAPACITY: usize = 1024;
+
+/// Board on which an event happens.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardKey {
+    pub cadence: Cadence,
+    /// The first day of the period of the board.
+    pub chosen_date: NaiveDate,
+    /// `None` for the global bingo.
+    pub room_id: Option<String>,
+}
+
+/// Progress of a user on a board.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    /// The user got the first AC for the problem in a cell.
+    CellSolved {
+        user_id: String,
+        board: BoardKey,
+        position: i32,
+        problem_id: String,
+        accepted_time: DateTime<Utc>,
+    },
+    /// The user completed a line in a level.
+    BingoAchieved {
+        user_id: String,
+        board: BoardKey,
+        level: usize,
+        /// The number of completed lines in the level.
+        lines: usize,
+        /// Whether or not all cells of the board are filled.
+        full_board: bool,
+    },
+}
+
+impl Event {
+    /// Name of the event in the stream.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::CellSolved { .. } => "cell_solved",
+            Event::BingoAchieved { .. } => "bingo_achieved",
+        }
+    }
+
+    pub fn user_id(&self) -> &str {
+        match self {
+            Event::CellSolved { user_id, .. } | Event::BingoAchieved { user_id, .. } => user_id,
+        }
+    }
+
+    pub fn board(&self) -> &BoardKey {
+        match self {
+            Event::CellSolved { board, .. } | Event::BingoAchieved { board, .. } => board,
+        }
+    }
+}
+
+/// Send `event` to the subscribers in every process.
+pub async fn publish(client: &DatabaseClient, event: &Event) -> Result<()> {
+    client.notify(CHANNEL, &serde_json::to_string(event)?).await
+}
+
+/// Events published by the updater, which are delivered to the subscribers in this process.
+/// Clones share the same subscribers.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+    token: CancellationToken,
+}
+
+impl EventBus {
+    /// Subscriptions end when `token` is cancelled.
+    pub fn new(token: CancellationToken) -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender, token }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+
+    /// Deliver `event` to the subscribers in this process.
+    pub fn send(&self, event: Event) {
+        // It fails only if there are no subscribers.
+        let _ = self.sender.send(event);
+    }
+
+    /// Cancelled when the subscriptions should end.
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Deliver the events published in any process until the connection fails.
+    pub async fn listen(&self) -> Result<()> {
+        database::listen(CHANNEL, |payload| match serde_json::from_str(payload) {
+            Ok(event) => self.send(event),
+            Err(e) => log::error!("Invalid event {payload:?}: {e}"),
+        })
+        .await
+    }
+}
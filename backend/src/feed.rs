@@ -0,0 +1,94 @@
+use crate::{bingo::BINGO_SIZE, database::models::Problem};
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Daily board shown as an entry of the feed.
+pub struct FeedEntry {
+    pub date: NaiveDate,
+    /// When the board starts.
+    pub published: DateTime<Utc>,
+    /// Problems in the order of the positions.
+    pub problems: Vec<Problem>,
+}
+
+/// Split problems ordered by the boards into the boards, each with the date it starts.
+pub fn group_by_date(problems: Vec<Problem>) -> Vec<(NaiveDate, Vec<Problem>)> {
+    let mut boards: Vec<(NaiveDate, Vec<Problem>)> = Vec::new();
+    for problem in problems {
+        match boards.last_mut() {
+            Some((date, problems))
+                if *date == problem.chosen_date && problems[0].cadence == problem.cadence =>
+            {
+                problems.push(problem)
+            }
+            _ => boards.push((problem.chosen_date, vec![problem])),
+        }
+    }
+    boards
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// HTML listing the problems of each level.
+fn entry_content(problems: &[Problem]) -> String {
+    let mut html = String::new();
+    for (level, problems) in problems.chunks(BINGO_SIZE).enumerate() {
+        html += &format!("<h3>Level {}</h3><ul>", level + 1);
+        for problem in problems {
+            html += &format!(
+                "<li><a href=\"{}\">{}</a> ({})</li>",
+                escape(&problem.url()),
+                escape(&problem.title),
+                problem.difficulty
+            );
+        }
+        html += "</ul>";
+    }
+    html
+}
+
+/// Atom feed of the daily boards, newest first.
+/// `self_url` is where the feed is served.
+pub fn render_atom(self_url: &str, entries: &[FeedEntry]) -> String {
+    let updated = entries
+        .iter()
+        .map(|entry| entry.published)
+        .max()
+        .unwrap_or(DateTime::UNIX_EPOCH);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml += "<feed xmlns=\"http://www.w3.org/2005/Atom\">\n";
+    xml += "  <id>tag:atcoder-bingo,2022:daily</id>\n";
+    xml += "  <title>AtCoder Bingo</title>\n";
+    xml += "  <subtitle>Problems of the daily bingo</subtitle>\n";
+    xml += &format!("  <link rel=\"self\" href=\"{}\"/>\n", escape(self_url));
+    xml += &format!("  <updated>{}</updated>\n", updated.to_rfc3339());
+    xml += "  <author><name>AtCoder Bingo</name></author>\n";
+    for entry in entries {
+        let published = entry.published.to_rfc3339();
+        xml += "  <entry>\n";
+        xml += &format!("    <id>tag:atcoder-bingo,2022:daily/{}</id>\n", entry.date);
+        xml += &format!("    <title>Daily bingo of {}</title>\n", entry.date);
+        xml += &format!("    <published>{published}</published>\n");
+        xml += &format!("    <updated>{published}</updated>\n");
+        xml += &format!(
+            "    <content type=\"html\">{}</content>\n",
+            escape(&entry_content(&entry.problems))
+        );
+        xml += "  </entry>\n";
+    }
+    xml += "</feed>\n";
+    xml
+}
@@ -0,0 +1,175 @@
+use crate::{
+    bingo::{count_lines, BINGO_SIZE, LINES},
+    database::models::{Problem, UserStatus},
+    feed::escape,
+};
+#[cfg(feature = "png")]
+use resvg::usvg::fontdb;
+#[cfg(feature = "png")]
+use std::sync::{Arc, LazyLock};
+
+const CELL_WIDTH: usize = 160;
+const CELL_HEIGHT: usize = 64;
+const MARGIN: usize = 16;
+const LEVEL_GAP: usize = 24;
+/// Height of the title above the levels.
+const HEADER_HEIGHT: usize = 40;
+/// Height of the label above each level.
+const LABEL_HEIGHT: usize = 24;
+/// Titles longer than this are truncated to fit in a cell.
+const MAX_TITLE_CHARS: usize = 16;
+
+/// Color of `difficulty` on AtCoder Problems.
+pub fn difficulty_color(difficulty: i32) -> &'static str {
+    match difficulty {
+        i32::MIN..=399 => "#808080",
+        400..=799 => "#804000",
+        800..=1199 => "#008000",
+        1200..=1599 => "#00c0c0",
+        1600..=1999 => "#0000ff",
+        2000..=2399 => "#c0c000",
+        2400..=2799 => "#ff8000",
+        _ => "#ff0000",
+    }
+}
+
+fn truncate(title: &str) -> String {
+    if title.chars().count() <= MAX_TITLE_CHARS {
+        title.to_string()
+    } else {
+        let mut truncated: String = title.chars().take(MAX_TITLE_CHARS - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+}
+
+/// Top-left corner of the cell at `position`.
+fn cell_origin(position: usize) -> (usize, usize) {
+    let level = position / BINGO_SIZE;
+    let (row, column) = (position % BINGO_SIZE / 3, position % 3);
+    (
+        MARGIN + level * (3 * CELL_WIDTH + LEVEL_GAP) + column * CELL_WIDTH,
+        HEADER_HEIGHT + LABEL_HEIGHT + row * CELL_HEIGHT,
+    )
+}
+
+fn cell_center(position: usize) -> (usize, usize) {
+    let (x, y) = cell_origin(position);
+    (x + CELL_WIDTH / 2, y + CELL_HEIGHT / 2)
+}
+
+/// SVG image of a board with each level as a grid.
+/// Cells solved in `user_status` are colored, and the completed lines are drawn.
+pub fn render_svg(title: &str, problems: &[Problem], user_status: &[UserStatus]) -> String {
+    let filled: Vec<bool> = problems
+        .iter()
+        .map(|problem| {
+            user_status
+                .iter()
+                .any(|status| status.problem_row_id == problem.id && status.accepted)
+        })
+        .collect();
+    let levels = problems.len().div_ceil(BINGO_SIZE);
+    let width = 2 * MARGIN + levels * 3 * CELL_WIDTH + levels.saturating_sub(1) * LEVEL_GAP;
+    let height = HEADER_HEIGHT + LABEL_HEIGHT + 3 * CELL_HEIGHT + MARGIN;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+        viewBox=\"0 0 {width} {height}\" font-family=\"sans-serif\">\n"
+    );
+    svg += &format!("<rect width=\"{width}\" height=\"{height}\" fill=\"#ffffff\"/>\n");
+    svg += &format!(
+        "<text x=\"{MARGIN}\" y=\"28\" font-size=\"18\" font-weight=\"bold\">{}</text>\n",
+        escape(title)
+    );
+
+    for level in 0..levels {
+        let (x, y) = cell_origin(level * BINGO_SIZE);
+        svg += &format!(
+            "<text x=\"{x}\" y=\"{}\" font-size=\"14\">Level {}</text>\n",
+            y - 8,
+            level + 1
+        );
+    }
+
+    for (position, problem) in problems.iter().enumerate() {
+        let (x, y) = cell_origin(position);
+        let fill = if filled[position] {
+            "#c8e6c9"
+        } else {
+            "#ffffff"
+        };
+        let color = difficulty_color(problem.difficulty);
+        svg += &format!(
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{CELL_WIDTH}\" height=\"{CELL_HEIGHT}\" \
+            fill=\"{fill}\" stroke=\"#999999\"/>\n"
+        );
+        svg += &format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"6\" fill=\"{color}\"/>\n",
+            x + 14,
+            y + 20
+        );
+        svg += &format!(
+            "<text x=\"{}\" y=\"{}\" font-size=\"13\" fill=\"{color}\">{}</text>\n",
+            x + 26,
+            y + 25,
+            escape(&truncate(&problem.title))
+        );
+        svg += &format!(
+            "<text x=\"{}\" y=\"{}\" font-size=\"11\" fill=\"#666666\">{}</text>\n",
+            x + 26,
+            y + 46,
+            problem.difficulty
+        );
+    }
+
+    // Draw the completed lines over the cells.
+    for (level, lines) in count_lines(&filled).into_iter().enumerate() {
+        if lines == 0 {
+            continue;
+        }
+        let level_start = level * BINGO_SIZE;
+        for line in LINES {
+            if !line
+                .iter()
+                .all(|&cell| filled.get(level_start + cell) == Some(&true))
+            {
+                continue;
+            }
+            let (x1, y1) = cell_center(level_start + line[0]);
+            let (x2, y2) = cell_center(level_start + line[2]);
+            svg += &format!(
+                "<line x1=\"{x1}\" y1=\"{y1}\" x2=\"{x2}\" y2=\"{y2}\" stroke=\"#e53935\" \
+                stroke-width=\"4\" stroke-linecap=\"round\" opacity=\"0.7\"/>\n"
+            );
+        }
+    }
+
+    svg += "</svg>\n";
+    svg
+}
+
+/// Fonts of the system, which are loaded once since it scans the font directories.
+#[cfg(feature = "png")]
+static FONTS: LazyLock<Arc<fontdb::Database>> = LazyLock::new(|| {
+    let mut fonts = fontdb::Database::new();
+    fonts.load_system_fonts();
+    Arc::new(fonts)
+});
+
+/// Rasterize `svg` for the services which do not accept SVG images such as OpenGraph.
+#[cfg(feature = "png")]
+pub fn render_png(svg: &str) -> anyhow::Result<Vec<u8>> {
+    use resvg::{tiny_skia, usvg};
+
+    let options = usvg::Options {
+        fontdb: FONTS.clone(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(svg, &options)?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| anyhow::anyhow!("The image is empty."))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    Ok(pixmap.encode_png()?)
+}
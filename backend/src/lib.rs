@@ -1,2 +1,21 @@
+pub mod auth;
+pub mod backfill;
+pub mod badge;
+pub mod bingo;
+pub mod calendar;
+pub mod chooser;
+pub mod config;
 pub mod crawler;
 pub mod database;
+pub mod events;
+pub mod feed;
+pub mod image;
+pub mod lockout;
+pub mod metrics;
+pub mod ranking;
+pub mod server;
+pub mod stats;
+pub mod streak;
+pub mod supervisor;
+pub mod updater;
+pub mod webhook;
@@ -0,0 +1,490 @@
+use crate::{
+    bingo::{self, SideBoard, Standing},
+    database::{
+        models::{Problem, Room, RoomMode},
+        DatabaseClient,
+    },
+    events::{Event, EventBus},
+};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    time::{interval, MissedTickBehavior},
+};
+
+/// Interval to check whether the time of the matches is over.
+const TICK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Time from when all members are ready until the match starts.
+pub const COUNTDOWN_SECS: i64 = 10;
+
+/// Events kept to be replayed to reconnecting clients.
+const LOG_CAPACITY: usize = 1000;
+
+/// Message from a client.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// The first message on a connection, where `user_id` is the name of its token.
+    /// Events after `last_seq` are replayed if given, and a snapshot is sent otherwise.
+    Join {
+        user_id: String,
+        last_seq: Option<u64>,
+    },
+    Ready,
+}
+
+/// Something that happens in a match, which is numbered in the order of occurrence.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MatchEvent {
+    Joined {
+        user_id: String,
+    },
+    Ready {
+        user_id: String,
+    },
+    /// All members are ready, and the match starts at `start_time`.
+    Countdown {
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    },
+    /// The user got the first AC in the cell.
+    CellClaimed {
+        position: usize,
+        user_id: String,
+    },
+    MatchEnd {
+        /// `None` for a draw.
+        winner: Option<String>,
+        standings: Vec<Standing>,
+    },
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SequencedEvent {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub event: MatchEvent,
+}
+
+/// Whole state of a match, which is sent when events cannot be replayed.
+#[derive(Clone, Debug, Serialize)]
+pub struct Snapshot {
+    /// Sequence number of the last event included.
+    pub seq: u64,
+    pub room: Room,
+    pub problems: Vec<Problem>,
+    pub members: Vec<String>,
+    pub ready: Vec<String>,
+    /// Owner of each cell.
+    pub claims: Vec<Option<String>>,
+    pub ended: bool,
+}
+
+/// Message to a client.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Snapshot(Snapshot),
+    Error { message: String },
+}
+
+/// Lockout match in a room, in which each member plays for themselves.
+pub struct MatchState {
+    room: Room,
+    problems: Vec<Problem>,
+    members: BTreeSet<String>,
+    joined: BTreeSet<String>,
+    ready: BTreeSet<String>,
+    boards: Vec<SideBoard>,
+    ended: bool,
+    /// Sequence number of the last event.
+    seq: u64,
+    log: VecDeque<SequencedEvent>,
+}
+
+impl MatchState {
+    /// `boards` are filled by the first AC of each member.
+    pub fn new(
+        room: Room,
+        problems: Vec<Problem>,
+        members: Vec<String>,
+        boards: Vec<SideBoard>,
+    ) -> Self {
+        Self {
+            room,
+            problems,
+            members: members.into_iter().collect(),
+            joined: BTreeSet::new(),
+            ready: BTreeSet::new(),
+            boards,
+            ended: false,
+            seq: 0,
+            log: VecDeque::new(),
+        }
+    }
+
+    pub fn room(&self) -> &Room {
+        &self.room
+    }
+
+    pub fn is_ended(&self) -> bool {
+        self.ended
+    }
+
+    fn push(&mut self, event: MatchEvent) -> SequencedEvent {
+        self.seq += 1;
+        let event = SequencedEvent {
+            seq: self.seq,
+            event,
+        };
+        if self.log.len() == LOG_CAPACITY {
+            self.log.pop_front();
+        }
+        self.log.push_back(event.clone());
+        event
+    }
+
+    /// Owner of each cell.
+    pub fn claims(&self) -> Vec<Option<String>> {
+        (0..self.problems.len())
+            .map(|position| {
+                self.boards
+                    .iter()
+                    .find(|board| board.filled.get(position) == Some(&true))
+                    .map(|board| board.side.clone())
+            })
+            .collect()
+    }
+
+    /// Replace the members with the latest ones, who may have joined the room since loaded.
+    pub fn set_members(&mut self, members: Vec<String>) {
+        self.members = members.into_iter().collect();
+    }
+
+    pub fn join(&mut self, user_id: &str) -> Result<Vec<SequencedEvent>, String> {
+        if !self.members.contains(user_id) {
+            return Err(format!("{user_id} is not a member of the room."));
+        }
+        if !self.joined.insert(user_id.to_string()) {
+            return Ok(Vec::new());
+        }
+        Ok(vec![self.push(MatchEvent::Joined {
+            user_id: user_id.to_string(),
+        })])
+    }
+
+    /// Mark `user_id` as ready.
+    /// When all members are ready, the room is rescheduled to start after the countdown.
+    pub fn ready(
+        &mut self,
+        user_id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<SequencedEvent>, String> {
+        if !self.members.contains(user_id) {
+            return Err(format!("{user_id} is not a member of the room."));
+        }
+        if self.ended || self.room.start_time <= now {
+            return Err("The match has already started.".to_string());
+        }
+        if !self.ready.insert(user_id.to_string()) {
+            return Ok(Vec::new());
+        }
+
+        let mut events = vec![self.push(MatchEvent::Ready {
+            user_id: user_id.to_string(),
+        })];
+        if self.ready == self.members {
+            let duration = self.room.end_time - self.room.start_time;
+            self.room.start_time = now + Duration::seconds(COUNTDOWN_SECS);
+            self.room.end_time = self.room.start_time + duration;
+            events.push(self.push(MatchEvent::Countdown {
+                start_time: self.room.start_time,
+                end_time: self.room.end_time,
+            }));
+        }
+        Ok(events)
+    }
+
+    /// Replace the boards with the latest ones, and end the match if it is decided.
+    pub fn update_boards(
+        &mut self,
+        boards: Vec<SideBoard>,
+        now: DateTime<Utc>,
+    ) -> Vec<SequencedEvent> {
+        if self.ended {
+            return Vec::new();
+        }
+
+        let old_claims = self.claims();
+        self.boards = boards;
+        let mut events = Vec::new();
+        for (position, (old, new)) in old_claims.into_iter().zip(self.claims()).enumerate() {
+            if let (None, Some(user_id)) = (old, new) {
+                events.push(self.push(MatchEvent::CellClaimed { position, user_id }));
+            }
+        }
+        events.append(&mut self.check_end(now));
+        events
+    }
+
+    /// End the match if the time is over, a member owns the majority of the cells,
+    /// or all cells are claimed.
+    pub fn check_end(&mut self, now: DateTime<Utc>) -> Vec<SequencedEvent> {
+        if self.ended {
+            return Vec::new();
+        }
+        let claims = self.claims();
+        let cells = claims.len();
+        let majority = self
+            .boards
+            .iter()
+            .any(|board| board.filled.iter().filter(|&&filled| filled).count() * 2 > cells);
+        let all_claimed = claims.iter().all(Option::is_some);
+        if now < self.room.end_time && !majority && !all_claimed {
+            return Vec::new();
+        }
+
+        self.ended = true;
+        let standings = bingo::standings(&self.boards);
+        let winners: Vec<&Standing> = standings
+            .iter()
+            .filter(|standing| standing.rank == 1 && standing.cells > 0)
+            .collect();
+        let winner = match winners[..] {
+            [winner] => Some(winner.side.clone()),
+            _ => None,
+        };
+        vec![self.push(MatchEvent::MatchEnd { winner, standings })]
+    }
+
+    /// Events after `seq` to be replayed.
+    /// Return `None` if some of them are no longer kept.
+    pub fn events_after(&self, seq: u64) -> Option<Vec<SequencedEvent>> {
+        if seq > self.seq {
+            return None;
+        }
+        match self.log.front() {
+            Some(first) if first.seq > seq + 1 => None,
+            _ => Some(
+                self.log
+                    .iter()
+                    .filter(|event| event.seq > seq)
+                    .cloned()
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            seq: self.seq,
+            room: self.room.clone(),
+            problems: self.problems.clone(),
+            members: self.members.iter().cloned().collect(),
+            ready: self.ready.iter().cloned().collect(),
+            claims: self.claims(),
+            ended: self.ended,
+        }
+    }
+}
+
+/// Match being played, shared by the connections to the room.
+pub struct Match {
+    state: Mutex<MatchState>,
+    sender: broadcast::Sender<SequencedEvent>,
+}
+
+impl Match {
+    fn new(state: MatchState) -> Self {
+        let (sender, _) = broadcast::channel(LOG_CAPACITY);
+        Self {
+            state: Mutex::new(state),
+            sender,
+        }
+    }
+
+    /// Send `events` to the connections.
+    /// This should be called under the lock of the state so that the events arrive in order.
+    fn broadcast(&self, events: &[SequencedEvent]) {
+        for event in events {
+            // It fails only if there are no subscribers.
+            let _ = self.sender.send(event.clone());
+        }
+    }
+
+    /// Subscribe to the events after `last_seq`.
+    /// The events to be replayed are returned, or a snapshot if they are no longer kept.
+    pub fn subscribe(
+        &self,
+        last_seq: Option<u64>,
+    ) -> (
+        Result<Vec<SequencedEvent>, Snapshot>,
+        broadcast::Receiver<SequencedEvent>,
+    ) {
+        let state = self.state.lock().unwrap();
+        let replay = last_seq
+            .and_then(|seq| state.events_after(seq))
+            .ok_or_else(|| state.snapshot());
+        (replay, self.sender.subscribe())
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        self.state.lock().unwrap().snapshot()
+    }
+}
+
+/// Lockout matches loaded in this process, which are kept up to date with the events.
+#[derive(Clone)]
+pub struct LockoutHub {
+    client: DatabaseClient,
+    matches: Arc<Mutex<HashMap<String, Arc<Match>>>>,
+}
+
+impl LockoutHub {
+    pub fn new(client: DatabaseClient) -> Self {
+        Self {
+            client,
+            matches: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Boards of the members filled by the first AC.
+    async fn fill_boards(&self, room_id: &str, problems: &[Problem]) -> Result<Vec<SideBoard>> {
+        let members = self.client.select_room_members(room_id).await?;
+        let user_status = self.client.select_user_status_by_room_id(room_id).await?;
+        let sides: HashMap<String, String> = members
+            .into_iter()
+            .map(|member| (member.user_id.clone(), member.user_id))
+            .collect();
+        Ok(bingo::fill_boards(problems, &sides, &user_status, true))
+    }
+
+    /// The match in the room.
+    /// Return `None` if the room does not exist or is not a lockout room.
+    pub async fn get(&self, room_id: &str) -> Result<Option<Arc<Match>>> {
+        if let Some(game) = self.matches.lock().unwrap().get(room_id) {
+            return Ok(Some(game.clone()));
+        }
+
+        let room = match self.client.select_room(room_id).await? {
+            Some(room) if room.mode == RoomMode::Lockout => room,
+            _ => return Ok(None),
+        };
+        let members = self.client.select_room_members(room_id).await?;
+        let problems = self.client.select_problems_by_room_id(room_id).await?;
+        let boards = self.fill_boards(room_id, &problems).await?;
+        let members = members.into_iter().map(|member| member.user_id).collect();
+        let mut state = MatchState::new(room, problems, members, boards);
+        state.check_end(Utc::now());
+
+        // Another connection may have loaded it in the meantime.
+        let game = self
+            .matches
+            .lock()
+            .unwrap()
+            .entry(room_id.to_string())
+            .or_insert_with(|| Arc::new(Match::new(state)))
+            .clone();
+        Ok(Some(game))
+    }
+
+    pub async fn join(
+        &self,
+        game: &Match,
+        user_id: &str,
+    ) -> Result<Result<Vec<SequencedEvent>, String>> {
+        let room_id = game.state.lock().unwrap().room().id.clone();
+        let members = self.client.select_room_members(&room_id).await?;
+        let mut state = game.state.lock().unwrap();
+        state.set_members(members.into_iter().map(|member| member.user_id).collect());
+        let result = state.join(user_id);
+        if let Ok(events) = &result {
+            game.broadcast(events);
+        }
+        Ok(result)
+    }
+
+    /// Mark `user_id` as ready, and store the new schedule when the countdown starts.
+    pub async fn ready(
+        &self,
+        game: &Match,
+        user_id: &str,
+    ) -> Result<Result<Vec<SequencedEvent>, String>> {
+        let (result, room) = {
+            let mut state = game.state.lock().unwrap();
+            let result = state.ready(user_id, Utc::now());
+            if let Ok(events) = &result {
+                game.broadcast(events);
+            }
+            (result, state.room().clone())
+        };
+        let counting_down = result.as_ref().is_ok_and(|events| {
+            events
+                .iter()
+                .any(|event| matches!(event.event, MatchEvent::Countdown { .. }))
+        });
+        if counting_down {
+            self.client.update_room_window(&room).await?;
+        }
+        Ok(result)
+    }
+
+    /// Apply the latest status of the room if its match is loaded.
+    async fn refresh(&self, room_id: &str) -> Result<()> {
+        let game = match self.matches.lock().unwrap().get(room_id) {
+            Some(game) => game.clone(),
+            None => return Ok(()),
+        };
+        let problems = game.state.lock().unwrap().problems.clone();
+        let boards = self.fill_boards(room_id, &problems).await?;
+        let mut state = game.state.lock().unwrap();
+        game.broadcast(&state.update_boards(boards, Utc::now()));
+        Ok(())
+    }
+
+    /// End the matches whose time is over, and forget the ended ones without connections.
+    fn tick(&self) {
+        let now = Utc::now();
+        self.matches.lock().unwrap().retain(|_, game| {
+            let mut state = game.state.lock().unwrap();
+            game.broadcast(&state.check_end(now));
+            !state.is_ended() || game.sender.receiver_count() > 0
+        });
+    }
+
+    /// Keep the matches up to date with the events until the subscription ends.
+    pub async fn run(&self, events: &EventBus) {
+        let mut receiver = events.subscribe();
+        let mut ticker = interval(TICK_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            let room_ids: Vec<String> = tokio::select! {
+                result = receiver.recv() => match result {
+                    Ok(Event::CellSolved { board, .. }) => board.room_id.into_iter().collect(),
+                    Ok(_) => continue,
+                    // Some solves may be missed, so refresh all matches.
+                    Err(RecvError::Lagged(_)) => self.matches.lock().unwrap().keys().cloned().collect(),
+                    Err(RecvError::Closed) => return,
+                },
+                _ = ticker.tick() => {
+                    self.tick();
+                    continue;
+                }
+                _ = events.token().cancelled() => return,
+            };
+            for room_id in room_ids {
+                if let Err(e) = self.refresh(&room_id).await {
+                    log::error!("Failed to refresh the match in room {room_id}: {e}");
+                }
+            }
+        }
+    }
+}
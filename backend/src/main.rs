@@ -0,0 +1,292 @@
+use anyhow::{anyhow, bail, Result};
+use atcoder_bingo_backend::{
+    backfill::backfill,
+    chooser,
+    config::Config,
+    database::DatabaseClient,
+    events::EventBus,
+    lockout::LockoutHub,
+    ranking, server, streak,
+    supervisor::{supervise, Backoff},
+    updater, webhook,
+};
+use chrono::NaiveDate;
+use clap::{Parser, Subcommand};
+use std::{future::Future, process};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Parser)]
+#[command(name = "atcoder-bingo", about = "Backend of AtCoder Bingo")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Serve the API.
+    Serve {
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+    },
+    /// Generate boards when a new period starts.
+    Choose {
+        /// Serve the metrics at this port.
+        #[arg(long)]
+        metrics_port: Option<u16>,
+    },
+    /// Apply recent submissions to user status.
+    UpdateUsers {
+        /// Serve the metrics at this port.
+        #[arg(long)]
+        metrics_port: Option<u16>,
+    },
+    /// Send the queued notifications to the webhooks.
+    DeliverWebhooks,
+    /// Refresh the rankings periodically.
+    RefreshRankings,
+    /// Apply all submissions on the boards from FROM to TO (inclusive).
+    Backfill { from: NaiveDate, to: NaiveDate },
+    /// Apply database migrations.
+    Migrate,
+    /// Calculate the streaks of all users from their solves.
+    RebuildStreaks,
+    /// Migrate, and then run the server and the background workers together.
+    All {
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+    },
+}
+
+/// Version of the migration which creates the empty table of the streaks.
+const STREAKS_MIGRATION: i32 = 8;
+
+/// Apply migrations, and fill the new tables which are calculated from the existing data.
+async fn migrate(client: &DatabaseClient) -> Result<Vec<i32>> {
+    let versions = client.migrate().await?;
+    if versions.contains(&STREAKS_MIGRATION) {
+        streak::rebuild_all(client).await?;
+    }
+    Ok(versions)
+}
+
+/// Cancel `token` on SIGINT or SIGTERM.
+/// Exit immediately on the second signal.
+fn cancel_on_signal(token: CancellationToken) -> Result<()> {
+    let mut sigterm = signal(SignalKind::terminate())?;
+    let mut sigint = signal(SignalKind::interrupt())?;
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = sigterm.recv() => {}
+            _ = sigint.recv() => {}
+        }
+        log::info!("Shutting down...");
+        token.cancel();
+
+        tokio::select! {
+            _ = sigterm.recv() => {}
+            _ = sigint.recv() => {}
+        }
+        log::warn!("Exit without waiting for the shutdown.");
+        process::exit(1);
+    });
+    Ok(())
+}
+
+/// Keep the lockout matches up to date until `token` is cancelled.
+async fn run_lockout(hub: LockoutHub, events: EventBus, token: CancellationToken) {
+    tokio::select! {
+        _ = hub.run(&events) => {}
+        _ = token.cancelled() => {}
+    }
+}
+
+/// Deliver the events published in any process to `events` until `token` is cancelled.
+async fn listen_events(events: EventBus, token: CancellationToken) {
+    tokio::select! {
+        result = events.listen() => {
+            if let Err(e) = result {
+                log::error!("Failed to listen to the events: {e}");
+            }
+        }
+        _ = token.cancelled() => {}
+    }
+}
+
+/// Run `worker` while serving the metrics at `metrics_port` if it is given.
+async fn with_metrics(metrics_port: Option<u16>, worker: impl Future<Output = ()>) -> Result<()> {
+    let Some(port) = metrics_port else {
+        worker.await;
+        return Ok(());
+    };
+    let server = server::run_metrics(port)?;
+    let handle = server.handle();
+    let server = tokio::spawn(server);
+    worker.await;
+    handle.stop(true).await;
+    server.await??;
+    Ok(())
+}
+
+/// Run the server until `token` is cancelled, and then wait for the requests in process.
+/// The events published by the updater are delivered to the server meanwhile.
+async fn serve(
+    config: Config,
+    client: DatabaseClient,
+    port: u16,
+    token: &CancellationToken,
+) -> Result<()> {
+    let events = EventBus::new(token.clone());
+    let listen = {
+        let events = events.clone();
+        move |token| listen_events(events.clone(), token)
+    };
+    let listener = tokio::spawn(supervise(
+        "listener",
+        token.clone(),
+        Backoff::default(),
+        listen,
+    ));
+
+    let hub = LockoutHub::new(client.clone());
+    let run_hub = {
+        let (hub, events) = (hub.clone(), events.clone());
+        move |token| run_lockout(hub.clone(), events.clone(), token)
+    };
+    let lockout = tokio::spawn(supervise(
+        "lockout",
+        token.clone(),
+        Backoff::default(),
+        run_hub,
+    ));
+
+    let server = server::run(config, client, events, hub, port)?;
+    let handle = server.handle();
+    let mut server = tokio::spawn(server);
+    let result = tokio::select! {
+        result = &mut server => match result {
+            Ok(Ok(())) => Err(anyhow!("The server stopped unexpectedly.")),
+            Ok(Err(e)) => Err(e.into()),
+            Err(e) => Err(e.into()),
+        },
+        _ = token.cancelled() => {
+            handle.stop(true).await;
+            server.await?.map_err(anyhow::Error::from)
+        }
+    };
+
+    token.cancel();
+    listener.await?;
+    lockout.await?;
+    result
+}
+
+/// Run everything in this process until `token` is cancelled or the server stops.
+/// The background workers are restarted whenever they fail.
+async fn run_all(
+    config: Config,
+    client: DatabaseClient,
+    port: u16,
+    token: CancellationToken,
+) -> Result<()> {
+    migrate(&client).await?;
+
+    let chooser = tokio::spawn(supervise("chooser", token.clone(), Backoff::default(), {
+        let (client, config) = (client.clone(), config.clone());
+        move |token| {
+            let (client, config) = (client.clone(), config.clone());
+            async move { chooser::run(&client, &config, &token).await }
+        }
+    }));
+    let updater = tokio::spawn(supervise("updater", token.clone(), Backoff::default(), {
+        let (client, config) = (client.clone(), config.clone());
+        move |token| {
+            let (client, config) = (client.clone(), config.clone());
+            async move { updater::run(&client, &config, &token).await }
+        }
+    }));
+
+    let deliverer = tokio::spawn(supervise("webhooks", token.clone(), Backoff::default(), {
+        let client = client.clone();
+        move |token| {
+            let client = client.clone();
+            async move { webhook::run(&client, &token).await }
+        }
+    }));
+
+    let ranker = tokio::spawn(supervise("rankings", token.clone(), Backoff::default(), {
+        let (client, config) = (client.clone(), config.clone());
+        move |token| {
+            let (client, config) = (client.clone(), config.clone());
+            async move { ranking::run(&client, &config, &token).await }
+        }
+    }));
+
+    let result = serve(config, client, port, &token).await;
+
+    // Stop the workers also when the server stops by itself.
+    token.cancel();
+    let (chooser_result, updater_result, deliverer_result, ranker_result) =
+        tokio::join!(chooser, updater, deliverer, ranker);
+    chooser_result?;
+    updater_result?;
+    deliverer_result?;
+    ranker_result?;
+    result
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::init();
+
+    let cli = Cli::parse();
+    let config = Config::from_env()?;
+    let token = CancellationToken::new();
+    cancel_on_signal(token.clone())?;
+
+    match cli.command {
+        Command::Serve { port } => {
+            let client = DatabaseClient::new().await;
+            serve(config, client, port, &token).await?;
+        }
+        Command::Choose { metrics_port } => {
+            let client = DatabaseClient::new().await;
+            with_metrics(metrics_port, chooser::run(&client, &config, &token)).await?;
+        }
+        Command::UpdateUsers { metrics_port } => {
+            let client = DatabaseClient::new().await;
+            with_metrics(metrics_port, updater::run(&client, &config, &token)).await?;
+        }
+        Command::DeliverWebhooks => {
+            let client = DatabaseClient::new().await;
+            webhook::run(&client, &token).await;
+        }
+        Command::RefreshRankings => {
+            let client = DatabaseClient::new().await;
+            ranking::run(&client, &config, &token).await;
+        }
+        Command::Backfill { from, to } => {
+            if from > to {
+                bail!("{from} is later than {to}.");
+            }
+            let client = DatabaseClient::new().await;
+            backfill(&client, &config, &from, &to).await?;
+            log::info!("Finished to backfill from {from} to {to}.");
+        }
+        Command::Migrate => {
+            let client = DatabaseClient::new().await;
+            let versions = migrate(&client).await?;
+            log::info!("{} migrations are applied.", versions.len());
+        }
+        Command::RebuildStreaks => {
+            let client = DatabaseClient::new().await;
+            streak::rebuild_all(&client).await?;
+        }
+        Command::All { port } => {
+            let client = DatabaseClient::new().await;
+            run_all(config, client, port, token).await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use prometheus::{
+    exponential_buckets, histogram_opts, opts, Gauge, Histogram, HistogramVec, IntCounter,
+    IntCounterVec, Registry, TextEncoder,
+};
+use std::sync::LazyLock;
+
+/// Metrics of this process.
+/// Workers running in other processes are not included.
+pub struct Metrics {
+    registry: Registry,
+    /// Time to get a response from AtCoder Problems by its HTTP status, or "error".
+    pub crawler_request_seconds: HistogramVec,
+    /// Size of the response bodies from AtCoder Problems.
+    pub crawler_response_bytes: IntCounter,
+    /// New submissions in each page of the recent submissions.
+    pub submissions_per_page: Histogram,
+    /// Unix time of the end of the last successful crawl, which shows when the crawler stalls.
+    pub last_crawl_timestamp_seconds: Gauge,
+    /// Writes of the user status by the operation, "insert" or "update".
+    pub user_status_writes: IntCounterVec,
+    /// Time to generate a board by its cadence and the result, "ok" or "error".
+    pub board_generation_seconds: HistogramVec,
+    /// Time to handle a request by its method, route pattern, and status.
+    pub http_request_seconds: HistogramVec,
+}
+
+impl Metrics {
+    fn new() -> prometheus::Result<Self> {
+        let metrics = Self {
+            registry: Registry::new(),
+            crawler_request_seconds: HistogramVec::new(
+                histogram_opts!(
+                    "bingo_crawler_request_duration_seconds",
+                    "Time to get a response from AtCoder Problems."
+                ),
+                &["status"],
+            )?,
+            crawler_response_bytes: IntCounter::with_opts(opts!(
+                "bingo_crawler_response_bytes_total",
+                "Size of the response bodies from AtCoder Problems."
+            ))?,
+            submissions_per_page: Histogram::with_opts(histogram_opts!(
+                "bingo_crawler_submissions_per_page",
+                "New submissions in each page of the recent submissions.",
+                vec![0.0, 1.0, 10.0, 100.0, 250.0, 500.0, 1000.0]
+            ))?,
+            last_crawl_timestamp_seconds: Gauge::with_opts(opts!(
+                "bingo_crawler_last_crawl_timestamp_seconds",
+                "Unix time of the end of the last successful crawl."
+            ))?,
+            user_status_writes: IntCounterVec::new(
+                opts!(
+                    "bingo_user_status_writes_total",
+                    "Writes of the user status."
+                ),
+                &["operation"],
+            )?,
+            board_generation_seconds: HistogramVec::new(
+                histogram_opts!(
+                    "bingo_board_generation_duration_seconds",
+                    "Time to generate a board.",
+                    exponential_buckets(0.5, 2.0, 8)?
+                ),
+                &["cadence", "result"],
+            )?,
+            http_request_seconds: HistogramVec::new(
+                histogram_opts!(
+                    "bingo_http_request_duration_seconds",
+                    "Time to handle an HTTP request."
+                ),
+                &["method", "route", "status"],
+            )?,
+        };
+
+        let registry = &metrics.registry;
+        registry.register(Box::new(metrics.crawler_request_seconds.clone()))?;
+        registry.register(Box::new(metrics.crawler_response_bytes.clone()))?;
+        registry.register(Box::new(metrics.submissions_per_page.clone()))?;
+        registry.register(Box::new(metrics.last_crawl_timestamp_seconds.clone()))?;
+        registry.register(Box::new(metrics.user_status_writes.clone()))?;
+        registry.register(Box::new(metrics.board_generation_seconds.clone()))?;
+        registry.register(Box::new(metrics.http_request_seconds.clone()))?;
+        Ok(metrics)
+    }
+
+    /// All metrics in the text format of Prometheus.
+    pub fn render(&self) -> String {
+        TextEncoder::new()
+            .encode_to_string(&self.registry.gather())
+            .expect("Failed to encode the metrics.")
+    }
+}
+
+static METRICS: LazyLock<Metrics> =
+    LazyLock::new(|| Metrics::new().expect("Failed to create the metrics."));
+
+/// Metrics of this process.
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
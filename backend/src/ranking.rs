@@ -0,0 +1,151 @@
+use crate::{
+    bingo::BINGO_SIZE,
+    config::Config,
+    database::{models::RankingEntry, DatabaseClient},
+    stats::line_masks,
+};
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use postgres_types::{FromSql, ToSql};
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+
+/// Interval to recalculate the rankings.
+const REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(600);
+
+/// Length of the rolling span in days, including today.
+const RECENT_DAYS: i64 = 30;
+
+/// The default number of the entries in a page.
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+
+/// The maximum number of the entries in a page.
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// Boards counted in a ranking.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSql, FromSql)]
+#[serde(rename_all = "snake_case")]
+#[postgres(name = "ranking_span")]
+pub enum RankingSpan {
+    #[default]
+    #[postgres(name = "all_time")]
+    AllTime,
+    /// Boards started in the last 30 days.
+    #[serde(rename = "last_30_days")]
+    #[postgres(name = "last_30_days")]
+    Last30Days,
+}
+
+impl RankingSpan {
+    pub const ALL: [RankingSpan; 2] = [RankingSpan::AllTime, RankingSpan::Last30Days];
+
+    /// The first day of the boards counted as of `today`, or `None` for all boards.
+    pub fn since(self, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            RankingSpan::AllTime => None,
+            RankingSpan::Last30Days => Some(today - Duration::days(RECENT_DAYS - 1)),
+        }
+    }
+}
+
+/// Key to order the users in a ranking, from the largest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RankingSort {
+    Bingos,
+    FullBoards,
+    Streak,
+    /// Sum of the difficulties of the solved cells.
+    #[default]
+    Score,
+}
+
+impl RankingSort {
+    /// Column of the `rankings` table.
+    pub fn column(self) -> &'static str {
+        match self {
+            RankingSort::Bingos => "bingos",
+            RankingSort::FullBoards => "full_boards",
+            RankingSort::Streak => "streak",
+            RankingSort::Score => "score",
+        }
+    }
+}
+
+/// A page of a ranking.
+#[derive(Clone, Debug, Serialize)]
+pub struct RankingPage {
+    pub span: RankingSpan,
+    pub sort: RankingSort,
+    /// 1-indexed.
+    pub page: i64,
+    pub per_page: i64,
+    /// The number of the users in the ranking.
+    pub total: i64,
+    /// `None` if the ranking has not been calculated yet.
+    pub refreshed_at: Option<DateTime<Utc>>,
+    pub entries: Vec<RankingEntry>,
+}
+
+/// Offset of the first entry in the `page`-th page, or `None` if it overflows.
+pub fn page_offset(page: i64, per_page: i64) -> Option<i64> {
+    page.checked_sub(1)?.checked_mul(per_page)
+}
+
+/// The `page`-th page of the stored ranking, with `per_page` entries in each page.
+/// `offset` is `page_offset(page, per_page)` checked by the caller.
+pub async fn select_page(
+    client: &DatabaseClient,
+    span: RankingSpan,
+    sort: RankingSort,
+    page: i64,
+    per_page: i64,
+    offset: i64,
+) -> Result<RankingPage> {
+    let (total, refreshed_at) = client.select_ranking_summary(span).await?;
+    let entries = client
+        .select_ranking_entries(span, sort, per_page, offset)
+        .await?;
+    Ok(RankingPage {
+        span,
+        sort,
+        page,
+        per_page,
+        total,
+        refreshed_at,
+        entries,
+    })
+}
+
+/// Recalculate all rankings from the user status.
+pub async fn refresh_all(client: &DatabaseClient, config: &Config) -> Result<()> {
+    let today = config.today();
+    for span in RankingSpan::ALL {
+        let users = client
+            .refresh_ranking(
+                span,
+                span.since(today),
+                today,
+                BINGO_SIZE as i32,
+                &line_masks(),
+            )
+            .await?;
+        log::info!("Refreshed the {span:?} ranking of {users} users.");
+    }
+    Ok(())
+}
+
+/// Refresh the rankings periodically until `token` is cancelled.
+pub async fn run(client: &DatabaseClient, config: &Config, token: &CancellationToken) {
+    while !token.is_cancelled() {
+        if let Err(e) = refresh_all(client, config).await {
+            log::error!("Failed to refresh the rankings: {e}");
+        }
+
+        tokio::select! {
+            _ = sleep(REFRESH_INTERVAL) => {}
+            _ = token.cancelled() => {}
+        }
+    }
+}
@@ -0,0 +1,130 @@
+mod admin;
+mod auth;
+mod badges;
+mod boards;
+mod error;
+mod events;
+mod feed;
+mod image;
+mod lockout;
+mod metrics;
+mod rankings;
+mod rooms;
+mod stats;
+
+use crate::{
+    bingo::BoardPeriod, config::Config, database::models, database::DatabaseClient,
+    events::EventBus, lockout::LockoutHub,
+};
+use actix_web::{dev::Server, web, App, HttpRequest, HttpResponse, HttpServer};
+pub use error::ApiError;
+
+/// Problems of the global board for `period`.
+/// Fail if the board has not been generated yet.
+async fn select_board(
+    client: &DatabaseClient,
+    period: &BoardPeriod,
+) -> Result<Vec<models::Problem>, ApiError> {
+    let problems = client.select_problems_by_period(period).await?;
+    if problems.is_empty() {
+        return Err(ApiError::NotFound(format!(
+            "The {} board from {} has not been generated yet.",
+            period.cadence, period.start
+        )));
+    }
+    Ok(problems)
+}
+
+/// Fail unless `user_id` matches `^[a-zA-Z0-9_]{0,16}$` like the form of the template.
+fn validate_user_id(user_id: &str) -> Result<(), ApiError> {
+    if user_id.len() <= 16
+        && user_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        Ok(())
+    } else {
+        Err(ApiError::BadRequest(format!(
+            "Invalid user ID: {user_id:?}"
+        )))
+    }
+}
+
+async fn not_found(req: HttpRequest) -> Result<HttpResponse, ApiError> {
+    Err(ApiError::NotFound(format!("{} is not found.", req.path())))
+}
+
+/// Start a server of only `/metrics` on `port`, for the workers run without the API server.
+/// The returned server ignores signals, and runs until it is stopped through its handle.
+pub fn run_metrics(port: u16) -> anyhow::Result<Server> {
+    let server = HttpServer::new(|| {
+        App::new()
+            .default_service(web::to(not_found))
+            .configure(metrics::configure)
+    })
+    .workers(1)
+    .bind(("0.0.0.0", port))?
+    .disable_signals()
+    .run();
+    Ok(server)
+}
+
+/// Start the API server on `port`.
+/// The returned server ignores signals, and runs until it is stopped through its handle.
+pub fn run(
+    config: Config,
+    client: DatabaseClient,
+    events: EventBus,
+    lockout: LockoutHub,
+    port: u16,
+) -> anyhow::Result<Server> {
+    // Wrap with web::Data
+    let config = web::Data::new(config);
+    let client = web::Data::new(client);
+    let events = web::Data::new(events);
+    let lockout = web::Data::new(lockout);
+
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(config.clone())
+            .app_data(client.clone())
+            .app_data(events.clone())
+            .app_data(lockout.clone())
+            // Render errors of extractors in the same format as the handlers.
+            .app_data(
+                web::JsonConfig::default()
+                    .error_handler(|e, _| ApiError::BadRequest(e.to_string()).into()),
+            )
+            .app_data(
+                web::QueryConfig::default()
+                    .error_handler(|e, _| ApiError::BadRequest(e.to_string()).into()),
+            )
+            .app_data(
+                web::PathConfig::default()
+                    .error_handler(|e, _| ApiError::NotFound(e.to_string()).into()),
+            )
+            .wrap(auth::Authentication)
+            // Outermost to measure the authentication too.
+            .wrap(metrics::RequestMetrics)
+            .default_service(web::to(not_found))
+            .configure(metrics::configure)
+            .service(
+                web::scope("/atcoder-bingo-api")
+                    .configure(admin::configure)
+                    .configure(badges::configure)
+                    // Before the boards, whose routes also match the images.
+                    .configure(image::configure)
+                    .configure(boards::configure)
+                    .configure(events::configure)
+                    .configure(feed::configure)
+                    .configure(lockout::configure)
+                    .configure(rankings::configure)
+                    .configure(rooms::configure)
+                    .configure(stats::configure),
+            )
+    })
+    .bind(("0.0.0.0", port))?
+    .disable_signals()
+    .run();
+    Ok(server)
+}
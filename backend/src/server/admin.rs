@@ -0,0 +1,469 @@
+use super::{boards::BoardView, select_board, ApiError};
+use crate::{
+    auth::{generate_token, hash_token, Principal, Scope},
+    backfill::backfill,
+    bingo::{self, BoardPeriod, Cadence, BINGO_SIZE},
+    chooser,
+    config::Config,
+    crawler::problems::get_problems,
+    database::{
+        models::{NewAuditEntry, WebhookFormat},
+        DatabaseClient,
+    },
+    events::EventBus,
+    webhook,
+};
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Audit log entry of the action of `principal`.
+/// It is written by the database with the change which the action makes.
+fn audit_entry(principal: &Principal, action: &str, detail: serde_json::Value) -> NewAuditEntry {
+    log::info!("{} did {action}: {detail}", principal.name);
+    NewAuditEntry {
+        actor: principal.name.clone(),
+        action: action.to_string(),
+        detail,
+    }
+}
+
+/// Record the action of `principal` in the audit log.
+async fn record(
+    client: &DatabaseClient,
+    principal: &Principal,
+    action: &str,
+    detail: serde_json::Value,
+) -> Result<(), ApiError> {
+    client
+        .insert_audit_entry(&audit_entry(principal, action, detail))
+        .await?;
+    Ok(())
+}
+
+#[post("/admin/boards/{cadence}/{date}/regenerate")]
+async fn regenerate_board(
+    req: HttpRequest,
+    principal: Principal,
+    path: web::Path<(Cadence, NaiveDate)>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    let (cadence, date) = path.into_inner();
+    log::info!("Request to regenerate the {cadence} board on {date}");
+    principal.require(Scope::GlobalAdmin)?;
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    let period = BoardPeriod::containing(cadence, date);
+    let audit = audit_entry(
+        &principal,
+        "regenerate_board",
+        json!({ "cadence": cadence, "start": period.start }),
+    );
+    chooser::regenerate(client, &period, &audit).await?;
+
+    let problems = select_board(client, &period).await?;
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&BoardView { period, problems }).unwrap()))
+}
+
+#[derive(Deserialize)]
+struct SwapCellRequest {
+    /// A problem in the band of the cell is chosen randomly if omitted.
+    problem_id: Option<String>,
+}
+
+#[put("/admin/boards/{cadence}/{date}/cells/{position}")]
+async fn swap_cell(
+    req: HttpRequest,
+    principal: Principal,
+    path: web::Path<(Cadence, NaiveDate, i32)>,
+    body: web::Json<SwapCellRequest>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    let (cadence, date, position) = path.into_inner();
+    log::info!("Request to swap cell {position} of the {cadence} board on {date}");
+    principal.require(Scope::GlobalAdmin)?;
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    let period = BoardPeriod::containing(cadence, date);
+    let board = select_board(client, &period).await?;
+    let mut cell = match board.iter().find(|problem| problem.position == position) {
+        Some(problem) => problem.clone(),
+        None => {
+            return Err(ApiError::NotFound(format!(
+                "Cell {position} does not exist."
+            )))
+        }
+    };
+
+    let problems = get_problems().await?;
+    let board_ids: Vec<&str> = board
+        .iter()
+        .map(|problem| problem.problem_id.as_str())
+        .collect();
+    let new_problem = match &body.problem_id {
+        Some(problem_id) if board_ids.contains(&problem_id.as_str()) => {
+            return Err(ApiError::BadRequest(format!(
+                "{problem_id:?} is already on the board."
+            )))
+        }
+        Some(problem_id) => problems
+            .into_iter()
+            .find(|problem| &problem.problem_id == problem_id)
+            .ok_or_else(|| ApiError::BadRequest(format!("Unknown problem: {problem_id:?}")))?,
+        None => {
+            let band = cadence.bands()[position as usize / BINGO_SIZE];
+            bingo::choose_replacement(&problems, &band, &board_ids, &mut rand::thread_rng())
+                .ok_or_else(|| {
+                    ApiError::BadRequest("The band has no other problems.".to_string())
+                })?
+        }
+    };
+
+    let old_problem_id = cell.problem_id.clone();
+    cell.problem_id = new_problem.problem_id;
+    cell.contest_id = new_problem.contest_id;
+    cell.title = new_problem.title;
+    cell.difficulty = new_problem.difficulty;
+    let audit = audit_entry(
+        &principal,
+        "swap_cell",
+        json!({
+            "cadence": cadence,
+            "start": period.start,
+            "position": position,
+            "old_problem_id": old_problem_id,
+            "new_problem_id": cell.problem_id,
+        }),
+    );
+    client.update_problem(&cell, &audit).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&cell).unwrap()))
+}
+
+#[delete("/admin/boards/{cadence}/{date}")]
+async fn delete_board(
+    req: HttpRequest,
+    principal: Principal,
+    path: web::Path<(Cadence, NaiveDate)>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    let (cadence, date) = path.into_inner();
+    log::info!("Request to delete the {cadence} board on {date}");
+    principal.require(Scope::GlobalAdmin)?;
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    let period = BoardPeriod::containing(cadence, date);
+    let audit = audit_entry(
+        &principal,
+        "delete_board",
+        json!({ "cadence": cadence, "start": period.start }),
+    );
+    if client.delete_problems_by_period(&period, &audit).await? == 0 {
+        return Err(ApiError::NotFound(format!(
+            "The {cadence} board from {} does not exist.",
+            period.start
+        )));
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(Deserialize)]
+struct RecrawlRequest {
+    from: NaiveDate,
+    to: NaiveDate,
+}
+
+/// Apply the submissions on the boards from `from` to `to` again in the background.
+#[post("/admin/recrawl")]
+async fn recrawl(
+    req: HttpRequest,
+    principal: Principal,
+    body: web::Json<RecrawlRequest>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    let RecrawlRequest { from, to } = body.into_inner();
+    log::info!("Request to recrawl from {from} to {to}");
+    principal.require(Scope::GlobalAdmin)?;
+
+    // Get database client, config, and event bus from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+    let config = req.app_data::<web::Data<Config>>().unwrap();
+    let bus = req.app_data::<web::Data<EventBus>>().unwrap();
+
+    if from > to {
+        return Err(ApiError::BadRequest(format!("{from} is later than {to}.")));
+    }
+    record(
+        client,
+        &principal,
+        "recrawl",
+        json!({ "from": from, "to": to }),
+    )
+    .await?;
+
+    // The recrawl is abandoned on shutdown. Its progress is logged by the backfill.
+    let (client, config, token) = (client.clone(), config.clone(), bus.token().clone());
+    tokio::spawn(async move {
+        tokio::select! {
+            result = backfill(&client, &config, &from, &to) => match result {
+                Ok(()) => log::info!("Finished to recrawl from {from} to {to}."),
+                Err(e) => log::error!("Failed to recrawl from {from} to {to}: {e}"),
+            },
+            _ = token.cancelled() => {
+                log::warn!("The recrawl from {from} to {to} is cancelled by the shutdown.");
+            }
+        }
+    });
+
+    Ok(HttpResponse::Accepted().finish())
+}
+
+#[derive(Deserialize)]
+struct AuditLogQuery {
+    #[serde(default = "default_audit_log_limit")]
+    limit: i64,
+}
+
+fn default_audit_log_limit() -> i64 {
+    100
+}
+
+#[get("/admin/audit-log")]
+async fn audit_log(
+    req: HttpRequest,
+    principal: Principal,
+    query: web::Query<AuditLogQuery>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for the audit log");
+    principal.require(Scope::GlobalAdmin)?;
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    let entries = client
+        .select_newest_audit_entries(query.limit.clamp(1, 1000))
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&entries).unwrap()))
+}
+
+#[derive(Deserialize)]
+struct CreateTokenRequest {
+    name: String,
+    scopes: Vec<Scope>,
+}
+
+#[derive(Serialize)]
+struct CreatedToken {
+    id: i32,
+    /// Shown only once, since only its hash is stored.
+    token: String,
+}
+
+#[post("/admin/tokens")]
+async fn create_token(
+    req: HttpRequest,
+    principal: Principal,
+    body: web::Json<CreateTokenRequest>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request to create a token for {}", body.name);
+    principal.require(Scope::GlobalAdmin)?;
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    if body.name.is_empty() || body.scopes.is_empty() {
+        return Err(ApiError::BadRequest(
+            "A token must have a name and some scopes.".to_string(),
+        ));
+    }
+    let token = generate_token();
+    let id = client
+        .insert_api_token(&body.name, &hash_token(&token), &body.scopes)
+        .await?;
+    record(
+        client,
+        &principal,
+        "create_token",
+        json!({ "id": id, "name": body.name, "scopes": body.scopes }),
+    )
+    .await?;
+
+    Ok(HttpResponse::Created()
+        .content_type("application/json")
+        .body(serde_json::to_string(&CreatedToken { id, token }).unwrap()))
+}
+
+#[get("/admin/tokens")]
+async fn tokens(
+    req: HttpRequest,
+    principal: Principal,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for the tokens");
+    principal.require(Scope::GlobalAdmin)?;
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    let tokens = client.select_api_tokens().await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&tokens).unwrap()))
+}
+
+#[delete("/admin/tokens/{id}")]
+async fn revoke_token(
+    req: HttpRequest,
+    principal: Principal,
+    id: web::Path<i32>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request to revoke token {id}");
+    principal.require(Scope::GlobalAdmin)?;
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    if !client.revoke_api_token(*id).await? {
+        return Err(ApiError::NotFound(format!(
+            "Active token {id} does not exist."
+        )));
+    }
+    record(client, &principal, "revoke_token", json!({ "id": *id })).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(Deserialize)]
+struct CreateWebhookRequest {
+    url: String,
+    #[serde(default = "default_webhook_format")]
+    format: WebhookFormat,
+    /// The webhook is notified of the global boards if omitted.
+    room_id: Option<String>,
+    /// Generated if omitted.
+    secret: Option<String>,
+}
+
+fn default_webhook_format() -> WebhookFormat {
+    WebhookFormat::Json
+}
+
+#[derive(Serialize)]
+struct CreatedWebhook {
+    id: i32,
+    /// Shown only once to verify the signatures.
+    secret: String,
+}
+
+/// Webhooks of a room can be created by room admins.
+#[post("/admin/webhooks")]
+async fn create_webhook(
+    req: HttpRequest,
+    principal: Principal,
+    body: web::Json<CreateWebhookRequest>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request to create a webhook for {}", body.url);
+    let body = body.into_inner();
+    match body.room_id {
+        Some(_) => principal.require(Scope::RoomAdmin)?,
+        None => principal.require(Scope::GlobalAdmin)?,
+    }
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    if let Err(e) = webhook::check_url(&body.url).await {
+        return Err(ApiError::BadRequest(format!(
+            "Invalid URL {:?}: {e}",
+            body.url
+        )));
+    }
+    if let Some(room_id) = &body.room_id {
+        if client.select_room(room_id).await?.is_none() {
+            return Err(ApiError::NotFound(format!(
+                "Room {room_id} does not exist."
+            )));
+        }
+    }
+    let secret = match body.secret {
+        Some(secret) if !secret.is_empty() => secret,
+        _ => generate_token(),
+    };
+    let id = client
+        .insert_webhook(&body.url, body.format, &secret, body.room_id.as_deref())
+        .await?;
+    record(
+        client,
+        &principal,
+        "create_webhook",
+        json!({ "id": id, "url": body.url, "format": body.format, "room_id": body.room_id }),
+    )
+    .await?;
+
+    Ok(HttpResponse::Created()
+        .content_type("application/json")
+        .body(serde_json::to_string(&CreatedWebhook { id, secret }).unwrap()))
+}
+
+#[get("/admin/webhooks")]
+async fn webhooks(
+    req: HttpRequest,
+    principal: Principal,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for the webhooks");
+    principal.require(Scope::GlobalAdmin)?;
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    let webhooks = client.select_webhooks().await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&webhooks).unwrap()))
+}
+
+#[delete("/admin/webhooks/{id}")]
+async fn delete_webhook(
+    req: HttpRequest,
+    principal: Principal,
+    id: web::Path<i32>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request to delete webhook {id}");
+    principal.require(Scope::GlobalAdmin)?;
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    if !client.delete_webhook(*id).await? {
+        return Err(ApiError::NotFound(format!("Webhook {id} does not exist.")));
+    }
+    record(client, &principal, "delete_webhook", json!({ "id": *id })).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub(super) fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(regenerate_board)
+        .service(swap_cell)
+        .service(delete_board)
+        .service(recrawl)
+        .service(audit_log)
+        .service(create_token)
+        .service(tokens)
+        .service(revoke_token)
+        .service(create_webhook)
+        .service(webhooks)
+        .service(delete_webhook);
+}
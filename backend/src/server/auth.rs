@@ -0,0 +1,131 @@
+use super::ApiError;
+use crate::{
+    auth::{hash_token, Principal, Scope},
+    config::Config,
+    database::DatabaseClient,
+};
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header,
+    web, FromRequest, HttpMessage, HttpRequest,
+};
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+};
+
+/// Middleware which identifies the owner of the bearer token in the `Authorization` header.
+/// Requests with an unknown token are rejected, and those without a token pass anonymously.
+pub(super) struct Authentication;
+
+impl<S, B> Transform<S, ServiceRequest> for Authentication
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = AuthenticationMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuthenticationMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub(super) struct AuthenticationMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for AuthenticationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        Box::pin(async move {
+            match authenticate(&req).await {
+                Ok(Some(principal)) => {
+                    req.extensions_mut().insert(principal);
+                }
+                Ok(None) => {}
+                Err(e) => return Ok(req.error_response(e).map_into_right_body()),
+            }
+            let res = service.call(req).await?;
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+/// Owner of the token of `req`, or `None` if `req` has no token.
+async fn authenticate(req: &ServiceRequest) -> Result<Option<Principal>, ApiError> {
+    let token = match req.headers().get(header::AUTHORIZATION) {
+        Some(value) => value
+            .to_str()
+            .ok()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| ApiError::Unauthorized("Malformed Authorization header.".to_string()))?,
+        None => return Ok(None),
+    };
+
+    // Get database client and config from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+    let config = req.app_data::<web::Data<Config>>().unwrap();
+
+    if let Some(principal) = config.static_principal(token) {
+        return Ok(Some(principal));
+    }
+    match client
+        .select_active_api_token_by_hash(&hash_token(token))
+        .await?
+    {
+        Some(api_token) => Ok(Some(Principal {
+            name: api_token.name,
+            scopes: api_token.scopes,
+        })),
+        None => Err(ApiError::Unauthorized("Invalid token.".to_string())),
+    }
+}
+
+impl Principal {
+    /// Fail unless the token has `scope`.
+    pub(super) fn require(&self, scope: Scope) -> Result<(), ApiError> {
+        if self.has_scope(scope) {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden(format!(
+                "The token of {} does not have the {scope} scope.",
+                self.name
+            )))
+        }
+    }
+}
+
+/// Extract the owner of the token identified by `Authentication`.
+/// Requests without a token are rejected.
+impl FromRequest for Principal {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, ApiError>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<Principal>()
+                .cloned()
+                .ok_or_else(|| ApiError::Unauthorized("A token is required.".to_string())),
+        )
+    }
+}
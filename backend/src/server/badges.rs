@@ -0,0 +1,63 @@
+use super::{validate_user_id, ApiError};
+use crate::{
+    badge::render_badge,
+    config::Config,
+    database::DatabaseClient,
+    stats::{progress_by_board, BadgeStats},
+    streak::{self, StreakKind},
+};
+use actix_web::{get, http::header, web, HttpRequest, HttpResponse, Responder};
+use sha2::{Digest, Sha256};
+
+/// Badges are embedded in pages of other sites, which may fetch them on every view.
+const CACHE_CONTROL: &str = "public, max-age=300";
+
+#[get("/badges/{user_id}.svg")]
+async fn user_badge(
+    req: HttpRequest,
+    user_id: web::Path<String>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for the badge of {user_id}");
+
+    // Get database client and config from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+    let config = req.app_data::<web::Data<Config>>().unwrap();
+
+    validate_user_id(&user_id)?;
+    let today = config.today();
+    let solved = client.select_solved_global_problems(&user_id).await?;
+    let streak = streak::select(client, &user_id)
+        .await?
+        .get(&StreakKind::Bingo)
+        .map_or(0, |streak| streak.current_at(today));
+    let stats = BadgeStats::new(&progress_by_board(&solved), today, streak);
+    let message = format!(
+        "today {} | streak {} | total {}",
+        stats.today, stats.streak, stats.lifetime
+    );
+    let color = if stats.today > 0 { "#4c1" } else { "#007ec6" };
+    let svg = render_badge("AtCoder Bingo", &message, color);
+
+    let etag = format!("\"{:x}\"", Sha256::digest(svg.as_bytes()));
+    let not_modified = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+    let mut response = if not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    response
+        .insert_header((header::CACHE_CONTROL, CACHE_CONTROL))
+        .insert_header((header::ETAG, etag));
+    if not_modified {
+        return Ok(response.finish());
+    }
+    Ok(response.content_type("image/svg+xml").body(svg))
+}
+
+pub(super) fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(user_badge);
+}
@@ -0,0 +1,180 @@
+use super::{select_board, validate_user_id, ApiError};
+use crate::{
+    bingo::{BoardPeriod, Cadence},
+    config::Config,
+    database::{models, DatabaseClient},
+};
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+struct UserStatusQuery {
+    user_id: Option<String>,
+}
+
+#[get("/user-status/today")]
+async fn user_status_today(
+    req: HttpRequest,
+    query: web::Query<UserStatusQuery>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for today's user status");
+
+    // Get database client and config from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+    let config = req.app_data::<web::Data<Config>>().unwrap();
+
+    // Get the problem IDs.
+    let today = config.today();
+    let period = BoardPeriod::containing(Cadence::Daily, today);
+    let problems = select_board(client, &period).await?;
+    let problem_row_ids: Vec<i32> = problems.iter().map(|problem| problem.id).collect();
+
+    // Filter user submissions
+    let user_status = match &query.user_id {
+        Some(user_id) => {
+            validate_user_id(user_id)?;
+            client
+                .select_user_status_by_user_id_and_problem_row_ids(user_id, &problem_row_ids)
+                .await?
+        }
+        None => {
+            client
+                .select_user_status_by_problem_row_ids(&problem_row_ids)
+                .await?
+        }
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&user_status).unwrap()))
+}
+
+#[get("/users/{user_id}/status/{date}")]
+async fn user_status_of_date(
+    req: HttpRequest,
+    path: web::Path<(String, NaiveDate)>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    let (user_id, date) = path.into_inner();
+    log::info!("Request for user status of {user_id} on {date}");
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    validate_user_id(&user_id)?;
+    let period = BoardPeriod::containing(Cadence::Daily, date);
+    let problems = select_board(client, &period).await?;
+    let problem_row_ids: Vec<i32> = problems.iter().map(|problem| problem.id).collect();
+    let user_status = client
+        .select_user_status_by_user_id_and_problem_row_ids(&user_id, &problem_row_ids)
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&user_status).unwrap()))
+}
+
+#[get("/problems/today")]
+async fn problem_today(req: HttpRequest) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for today's problems");
+
+    // Get database client and config from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+    let config = req.app_data::<web::Data<Config>>().unwrap();
+
+    // Filter today's problems
+    let today = config.today();
+    let period = BoardPeriod::containing(Cadence::Daily, today);
+    let problems = select_board(client, &period).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&problems).unwrap()))
+}
+
+#[derive(Serialize)]
+pub(super) struct BoardView {
+    pub(super) period: BoardPeriod,
+    pub(super) problems: Vec<models::Problem>,
+}
+
+#[get("/boards/{cadence}/current")]
+async fn current_board(
+    req: HttpRequest,
+    cadence: web::Path<Cadence>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for the current {cadence:?} board");
+
+    // Get database client and config from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+    let config = req.app_data::<web::Data<Config>>().unwrap();
+
+    let today = config.today();
+    let period = BoardPeriod::containing(*cadence, today);
+    let problems = select_board(client, &period).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&BoardView { period, problems }).unwrap()))
+}
+
+#[get("/boards/{cadence}/current/user-status")]
+async fn current_board_user_status(
+    req: HttpRequest,
+    cadence: web::Path<Cadence>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for user status of the current {cadence:?} board");
+
+    // Get database client and config from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+    let config = req.app_data::<web::Data<Config>>().unwrap();
+
+    let today = config.today();
+    let period = BoardPeriod::containing(*cadence, today);
+    let problems = select_board(client, &period).await?;
+    let problem_row_ids: Vec<i32> = problems.iter().map(|problem| problem.id).collect();
+    let user_status = client
+        .select_user_status_by_problem_row_ids(&problem_row_ids)
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&user_status).unwrap()))
+}
+
+#[get("/boards/{cadence}/{date}")]
+async fn board_of_date(
+    req: HttpRequest,
+    path: web::Path<(Cadence, NaiveDate)>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    let (cadence, date) = path.into_inner();
+    log::info!("Request for the {cadence} board on {date}");
+
+    // Get database client and config from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+    let config = req.app_data::<web::Data<Config>>().unwrap();
+
+    // Boards generated ahead are hidden until they start.
+    let period = BoardPeriod::containing(cadence, date);
+    if period.start > config.today() {
+        return Err(ApiError::NotFound(format!(
+            "The {cadence} board from {} has not started yet.",
+            period.start
+        )));
+    }
+    let problems = select_board(client, &period).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&BoardView { period, problems }).unwrap()))
+}
+
+pub(super) fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(problem_today)
+        .service(user_status_today)
+        .service(user_status_of_date)
+        .service(current_board)
+        .service(current_board_user_status)
+        // After the routes of the current boards, which this also matches.
+        .service(board_of_date);
+}
@@ -0,0 +1,86 @@
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use deadpool_postgres::PoolError;
+use serde::Serialize;
+use thiserror::Error;
+
+/// Error returned by the API.
+/// Every error is rendered as JSON like `{"error": "not_found", "message": "..."}`.
+#[derive(Error, Debug)]
+pub enum ApiError {
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("{0}")]
+    Unauthorized(String),
+    #[error("{0}")]
+    Forbidden(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("The database is unavailable.")]
+    DatabaseUnavailable(#[source] anyhow::Error),
+    #[error("Internal server error.")]
+    Internal(#[source] anyhow::Error),
+}
+
+impl ApiError {
+    fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::DatabaseUnavailable(_) => "database_unavailable",
+            ApiError::Internal(_) => "internal",
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        // Distinguish errors due to a lost connection from other database errors.
+        let unavailable = if let Some(db_error) = e.downcast_ref::<tokio_postgres::Error>() {
+            db_error.is_closed() || db_error.code().is_none()
+        } else {
+            // Connections are taken from the pool, which fails to connect or to wait for one.
+            matches!(
+                e.downcast_ref::<PoolError>(),
+                Some(PoolError::Backend(_) | PoolError::Timeout(_) | PoolError::Closed)
+            )
+        };
+        if unavailable {
+            ApiError::DatabaseUnavailable(e)
+        } else {
+            ApiError::Internal(e)
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    error: &'a str,
+    message: String,
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::DatabaseUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            ApiError::DatabaseUnavailable(e) | ApiError::Internal(e) => log::error!("{e:?}"),
+            _ => {}
+        }
+
+        HttpResponse::build(self.status_code()).json(ErrorBody {
+            error: self.code(),
+            message: self.to_string(),
+        })
+    }
+}
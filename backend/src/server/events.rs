@@ -0,0 +1,105 @@
+use super::{validate_user_id, ApiError};
+use crate::{
+    bingo::Cadence,
+    config::Config,
+    events::{Event, EventBus},
+};
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use futures_util::stream;
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::{
+    sync::broadcast::error::RecvError,
+    time::{interval, MissedTickBehavior},
+};
+
+/// Comments are sent in this interval so that proxies keep the connection.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Deserialize)]
+struct EventQuery {
+    user_id: Option<String>,
+    /// The events on the board of the room instead of today's daily board.
+    room_id: Option<String>,
+}
+
+impl EventQuery {
+    fn matches(&self, event: &Event, config: &Config) -> bool {
+        let board = event.board();
+        let on_board = match &self.room_id {
+            Some(room_id) => board.room_id.as_ref() == Some(room_id),
+            None => {
+                board.room_id.is_none()
+                    && board.cadence == Cadence::Daily
+                    && board.chosen_date == config.today()
+            }
+        };
+        let by_user = match &self.user_id {
+            Some(user_id) => event.user_id() == user_id,
+            None => true,
+        };
+        on_board && by_user
+    }
+}
+
+/// Format `event` as a message of Server-Sent Events.
+fn to_message(event: &Event) -> web::Bytes {
+    let data = serde_json::to_string(event).unwrap();
+    web::Bytes::from(format!("event: {}\ndata: {data}\n\n", event.name()))
+}
+
+#[get("/events/today")]
+async fn events_today(
+    req: HttpRequest,
+    query: web::Query<EventQuery>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for the events of today");
+
+    // Get event bus and config from state
+    let bus = req.app_data::<web::Data<EventBus>>().unwrap();
+    let config = req.app_data::<web::Data<Config>>().unwrap();
+
+    let query = query.into_inner();
+    if let Some(user_id) = &query.user_id {
+        validate_user_id(user_id)?;
+    }
+
+    let mut keep_alive = interval(KEEP_ALIVE_INTERVAL);
+    keep_alive.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let state = (
+        bus.subscribe(),
+        keep_alive,
+        bus.token().clone(),
+        config.clone(),
+        query,
+    );
+    let events = stream::unfold(state, |mut state| async move {
+        let (receiver, keep_alive, token, config, query) = &mut state;
+        loop {
+            let message = tokio::select! {
+                result = receiver.recv() => match result {
+                    Ok(event) if query.matches(&event, config) => to_message(&event),
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("{skipped} events are skipped for a slow client.");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return None,
+                },
+                _ = keep_alive.tick() => web::Bytes::from_static(b": keep-alive\n\n"),
+                // Close the stream so that the server can shut down.
+                _ = token.cancelled() => return None,
+            };
+            return Some((Ok::<_, actix_web::Error>(message), state));
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(events))
+}
+
+pub(super) fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(events_today);
+}
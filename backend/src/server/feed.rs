@@ -0,0 +1,122 @@
+use super::ApiError;
+use crate::{
+    calendar::{render_ics, CalendarEvent},
+    config::Config,
+    database::DatabaseClient,
+    feed::{group_by_date, render_atom, FeedEntry},
+};
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use chrono::{Duration, Utc};
+
+/// The number of the boards in the feed.
+const FEED_DAYS: i64 = 30;
+
+/// Boards which ended within this number of days are kept in the calendar.
+const CALENDAR_PAST_DAYS: i64 = 31;
+
+const CALENDAR_CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+
+#[get("/feed.atom")]
+async fn atom_feed(req: HttpRequest) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for the Atom feed");
+
+    // Get database client and config from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+    let config = req.app_data::<web::Data<Config>>().unwrap();
+
+    // Boards generated ahead are not shown until their day comes.
+    let problems = client
+        .select_recent_daily_problems(&config.today(), FEED_DAYS)
+        .await?;
+    let entries: Vec<FeedEntry> = group_by_date(problems)
+        .into_iter()
+        .map(|(date, problems)| FeedEntry {
+            date,
+            published: config.start_of_date(&date),
+            problems,
+        })
+        .collect();
+
+    let info = req.connection_info();
+    let self_url = format!("{}://{}{}", info.scheme(), info.host(), req.path());
+    Ok(HttpResponse::Ok()
+        .content_type("application/atom+xml; charset=utf-8")
+        .body(render_atom(&self_url, &entries)))
+}
+
+/// Events for the global boards, including the ones generated ahead.
+#[get("/calendar.ics")]
+async fn global_calendar(req: HttpRequest) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for the calendar of the boards");
+
+    // Get database client and config from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+    let config = req.app_data::<web::Data<Config>>().unwrap();
+
+    let today = config.today();
+    let problems = client
+        .select_global_problems_since(&(today - Duration::days(CALENDAR_PAST_DAYS)))
+        .await?;
+
+    let mut events = Vec::new();
+    for (start, mut problems) in group_by_date(problems) {
+        let (cadence, end) = (problems[0].cadence, problems[0].period_end);
+        // Do not reveal the problems before the board starts.
+        if start > today {
+            problems.clear();
+        }
+        events.push(CalendarEvent {
+            uid: format!("{cadence}-{start}@atcoder-bingo"),
+            summary: format!("AtCoder Bingo: {cadence} board from {start}"),
+            start: config.start_of_date(&start),
+            end: config.start_of_date(&(end + Duration::days(1))),
+            url: config.board_page_url(cadence, &start),
+            problems,
+        });
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type(CALENDAR_CONTENT_TYPE)
+        .body(render_ics("AtCoder Bingo", &events, &Utc::now())))
+}
+
+/// The event for the period in which the room is played.
+#[get("/rooms/{room_id}/calendar.ics")]
+async fn room_calendar(
+    req: HttpRequest,
+    room_id: web::Path<String>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for the calendar of room {room_id}");
+
+    // Get database client and config from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+    let config = req.app_data::<web::Data<Config>>().unwrap();
+
+    let room = match client.select_room(&room_id).await? {
+        Some(room) => room,
+        None => {
+            return Err(ApiError::NotFound(format!(
+                "Room {room_id} does not exist."
+            )))
+        }
+    };
+    let problems = client.select_problems_by_room_id(&room_id).await?;
+
+    let event = CalendarEvent {
+        uid: format!("room-{}@atcoder-bingo", room.id),
+        summary: format!("AtCoder Bingo: {}", room.name),
+        start: room.start_time,
+        end: room.end_time,
+        url: config.room_page_url(&room.id),
+        problems,
+    };
+    Ok(HttpResponse::Ok()
+        .content_type(CALENDAR_CONTENT_TYPE)
+        .body(render_ics(&room.name, &[event], &Utc::now())))
+}
+
+pub(super) fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(atom_feed)
+        .service(global_calendar)
+        .service(room_calendar);
+}
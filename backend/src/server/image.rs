@@ -0,0 +1,85 @@
+use super::{select_board, validate_user_id, ApiError};
+use crate::{
+    bingo::{BoardPeriod, Cadence},
+    config::Config,
+    database::DatabaseClient,
+    image::render_svg,
+};
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct ImageQuery {
+    /// An empty board is rendered if omitted.
+    user_id: Option<String>,
+}
+
+/// SVG image of the daily board on `date`.
+async fn board_svg(
+    req: &HttpRequest,
+    date: NaiveDate,
+    query: &ImageQuery,
+) -> Result<String, ApiError> {
+    // Get database client and config from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+    let config = req.app_data::<web::Data<Config>>().unwrap();
+
+    if date > config.today() {
+        return Err(ApiError::NotFound(format!(
+            "The daily board from {date} has not started yet."
+        )));
+    }
+    let period = BoardPeriod::containing(Cadence::Daily, date);
+    let problems = select_board(client, &period).await?;
+
+    let (title, user_status) = match &query.user_id {
+        Some(user_id) => {
+            validate_user_id(user_id)?;
+            let problem_row_ids: Vec<i32> = problems.iter().map(|problem| problem.id).collect();
+            let user_status = client
+                .select_user_status_by_user_id_and_problem_row_ids(user_id, &problem_row_ids)
+                .await?;
+            (format!("AtCoder Bingo {date}: {user_id}"), user_status)
+        }
+        None => (format!("AtCoder Bingo {date}"), Vec::new()),
+    };
+    Ok(render_svg(&title, &problems, &user_status))
+}
+
+#[get("/boards/{date}/image.svg")]
+async fn board_image_svg(
+    req: HttpRequest,
+    date: web::Path<NaiveDate>,
+    query: web::Query<ImageQuery>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for the image of the board on {date}");
+
+    let svg = board_svg(&req, *date, &query).await?;
+
+    Ok(HttpResponse::Ok().content_type("image/svg+xml").body(svg))
+}
+
+#[cfg(feature = "png")]
+#[get("/boards/{date}/image.png")]
+async fn board_image_png(
+    req: HttpRequest,
+    date: web::Path<NaiveDate>,
+    query: web::Query<ImageQuery>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for the PNG image of the board on {date}");
+
+    let svg = board_svg(&req, *date, &query).await?;
+    // Rasterizing takes a while, so it should not block the other requests.
+    let png = web::block(move || crate::image::render_png(&svg))
+        .await
+        .map_err(|e| ApiError::Internal(e.into()))??;
+
+    Ok(HttpResponse::Ok().content_type("image/png").body(png))
+}
+
+pub(super) fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(board_image_svg);
+    #[cfg(feature = "png")]
+    cfg.service(board_image_png);
+}
@@ -0,0 +1,179 @@
+use super::ApiError;
+use crate::{
+    auth::{Principal, Scope},
+    events::EventBus,
+    lockout::{ClientMessage, LockoutHub, Match, ServerMessage},
+};
+use actix_web::{get, rt, web, HttpRequest, HttpResponse};
+use actix_ws::{AggregatedMessage, AggregatedMessageStream, Closed, Session};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+
+async fn send(session: &mut Session, message: &impl Serialize) -> Result<(), Closed> {
+    session.text(serde_json::to_string(message).unwrap()).await
+}
+
+async fn send_error(session: &mut Session, message: String) -> Result<(), Closed> {
+    send(session, &ServerMessage::Error { message }).await
+}
+
+/// Wait for the next message from the client.
+/// Return `None` when the connection is closed.
+async fn receive(
+    stream: &mut AggregatedMessageStream,
+    session: &mut Session,
+) -> Option<ClientMessage> {
+    loop {
+        match stream.recv().await? {
+            Ok(AggregatedMessage::Text(text)) => match serde_json::from_str(&text) {
+                Ok(message) => return Some(message),
+                Err(e) => send_error(session, format!("Invalid message: {e}"))
+                    .await
+                    .ok()?,
+            },
+            Ok(AggregatedMessage::Ping(bytes)) => session.pong(&bytes).await.ok()?,
+            Ok(AggregatedMessage::Close(_)) => return None,
+            Ok(_) => {}
+            Err(e) => {
+                log::warn!("WebSocket protocol error: {e}");
+                return None;
+            }
+        }
+    }
+}
+
+/// Play the match as `player` through the connection until either side closes it.
+async fn play(
+    hub: LockoutHub,
+    game: Arc<Match>,
+    player: String,
+    session: &mut Session,
+    mut stream: AggregatedMessageStream,
+    token: CancellationToken,
+) -> Result<(), Closed> {
+    // The first message must be `join` as the player.
+    let (user_id, last_seq) = loop {
+        let message = tokio::select! {
+            message = receive(&mut stream, session) => message,
+            _ = token.cancelled() => None,
+        };
+        match message {
+            Some(ClientMessage::Join { user_id, last_seq }) if user_id == player => {
+                break (user_id, last_seq)
+            }
+            Some(ClientMessage::Join { user_id, .. }) => {
+                send_error(session, format!("{player} cannot join as {user_id}.")).await?
+            }
+            Some(_) => send_error(session, "Join the match first.".to_string()).await?,
+            None => return Ok(()),
+        }
+    };
+
+    // Subscribe before joining so that no events are missed.
+    let (replay, mut receiver) = game.subscribe(last_seq);
+    match hub.join(&game, &user_id).await {
+        Ok(Ok(_)) => {}
+        Ok(Err(message)) => return send_error(session, message).await,
+        Err(e) => {
+            log::error!("Failed to join {user_id} to the match: {e}");
+            return send_error(session, "Failed to join the match.".to_string()).await;
+        }
+    }
+    let mut seq = match replay {
+        Ok(events) => {
+            for event in &events {
+                send(session, event).await?;
+            }
+            events
+                .last()
+                .map_or(last_seq.unwrap_or(0), |event| event.seq)
+        }
+        Err(snapshot) => {
+            let seq = snapshot.seq;
+            send(session, &ServerMessage::Snapshot(snapshot)).await?;
+            seq
+        }
+    };
+
+    loop {
+        tokio::select! {
+            message = receive(&mut stream, session) => match message {
+                Some(ClientMessage::Ready) => match hub.ready(&game, &user_id).await {
+                    Ok(Ok(_)) => {}
+                    Ok(Err(message)) => send_error(session, message).await?,
+                    Err(e) => {
+                        log::error!("Failed to start the match: {e}");
+                        send_error(session, "Failed to start the match.".to_string())
+                            .await?;
+                    }
+                },
+                Some(ClientMessage::Join { .. }) => {
+                    send_error(session, "Already joined the match.".to_string()).await?
+                }
+                None => return Ok(()),
+            },
+            result = receiver.recv() => match result {
+                // Events up to `seq` are already sent by the replay or the snapshot.
+                Ok(event) if event.seq <= seq => {}
+                Ok(event) => {
+                    send(session, &event).await?;
+                    seq = event.seq;
+                }
+                Err(RecvError::Lagged(_)) => {
+                    let snapshot = game.snapshot();
+                    seq = snapshot.seq;
+                    send(session, &ServerMessage::Snapshot(snapshot)).await?;
+                }
+                Err(RecvError::Closed) => return Ok(()),
+            },
+            _ = token.cancelled() => return Ok(()),
+        }
+    }
+}
+
+/// The connection plays as the owner of the token, whose name must be their user ID.
+#[get("/rooms/{room_id}/match")]
+async fn room_match(
+    req: HttpRequest,
+    room_id: web::Path<String>,
+    principal: Principal,
+    body: web::Payload,
+) -> actix_web::Result<HttpResponse, ApiError> {
+    log::info!("Request for the match in room {room_id}");
+    principal.require(Scope::Read)?;
+
+    // Get lockout hub and event bus from state
+    let hub = req.app_data::<web::Data<LockoutHub>>().unwrap();
+    let bus = req.app_data::<web::Data<EventBus>>().unwrap();
+
+    let game = match hub.get(&room_id).await? {
+        Some(game) => game,
+        None => {
+            return Err(ApiError::NotFound(format!(
+                "Lockout room {room_id} does not exist."
+            )))
+        }
+    };
+    let (response, session, stream) =
+        actix_ws::handle(&req, body).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    let hub = hub.get_ref().clone();
+    let token = bus.token().clone();
+    rt::spawn(async move {
+        let mut session = session;
+        let stream = stream.aggregate_continuations();
+        if play(hub, game, principal.name, &mut session, stream, token)
+            .await
+            .is_ok()
+        {
+            let _ = session.close(None).await;
+        }
+    });
+    Ok(response)
+}
+
+pub(super) fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(room_match);
+}
@@ -0,0 +1,84 @@
+use crate::metrics::metrics;
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    get, web, HttpResponse, Responder,
+};
+use prometheus::TEXT_FORMAT;
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+    time::Instant,
+};
+
+/// Middleware which records the time to handle each request.
+/// Requests are labeled by the route pattern rather than the path to keep the series few.
+pub(super) struct RequestMetrics;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestMetrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = RequestMetricsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestMetricsMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub(super) struct RequestMetricsMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestMetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        Box::pin(async move {
+            let started_at = Instant::now();
+            let method = req.method().to_string();
+            let route = req
+                .match_pattern()
+                .unwrap_or_else(|| "unmatched".to_string());
+
+            let result = service.call(req).await;
+            let status = match &result {
+                Ok(res) => res.status(),
+                Err(e) => e.as_response_error().status_code(),
+            };
+            metrics()
+                .http_request_seconds
+                .with_label_values(&[method.as_str(), route.as_str(), status.as_str()])
+                .observe(started_at.elapsed().as_secs_f64());
+            result
+        })
+    }
+}
+
+#[get("/metrics")]
+async fn render_metrics() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type(TEXT_FORMAT)
+        .body(metrics().render())
+}
+
+pub(super) fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(render_metrics);
+}
@@ -0,0 +1,55 @@
+use super::ApiError;
+use crate::{
+    database::DatabaseClient,
+    ranking::{self, RankingSort, RankingSpan, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE},
+};
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct RankingQuery {
+    #[serde(default)]
+    span: RankingSpan,
+    #[serde(default)]
+    sort: RankingSort,
+    /// 1-indexed.
+    page: Option<i64>,
+    per_page: Option<i64>,
+}
+
+#[get("/rankings")]
+async fn rankings(
+    req: HttpRequest,
+    query: web::Query<RankingQuery>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!(
+        "Request for the {:?} ranking by {:?}",
+        query.span,
+        query.sort
+    );
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    let per_page = query.per_page.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&per_page) {
+        return Err(ApiError::BadRequest(format!(
+            "per_page must be from 1 to {MAX_PAGE_SIZE}."
+        )));
+    }
+    let page = query.page.unwrap_or(1);
+    let offset = match ranking::page_offset(page, per_page) {
+        Some(offset) if page >= 1 => offset,
+        _ => return Err(ApiError::BadRequest(format!("Invalid page: {page}"))),
+    };
+
+    let ranking =
+        ranking::select_page(client, query.span, query.sort, page, per_page, offset).await?;
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&ranking).unwrap()))
+}
+
+pub(super) fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(rankings);
+}
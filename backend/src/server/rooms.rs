@@ -0,0 +1,309 @@
+use super::{validate_user_id, ApiError};
+use crate::{
+    auth::{Principal, Scope},
+    bingo::{self, Band, Cadence, BINGO_SIZE, DEFAULT_BANDS},
+    config::Config,
+    crawler::problems::get_problems,
+    database::{models, DatabaseClient},
+};
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Deserialize)]
+struct CreateRoomRequest {
+    name: String,
+    #[serde(default = "default_room_mode")]
+    mode: models::RoomMode,
+    /// Members without a team.
+    #[serde(default)]
+    members: Vec<String>,
+    /// Members of each team.
+    #[serde(default)]
+    teams: HashMap<String, Vec<String>>,
+    /// The daily bingo's bands are used if omitted.
+    bands: Option<Vec<Band>>,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+}
+
+fn default_room_mode() -> models::RoomMode {
+    models::RoomMode::Individual
+}
+
+#[derive(Deserialize)]
+struct JoinRoomRequest {
+    user_id: String,
+    team: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RoomView {
+    room: models::Room,
+    members: Vec<models::RoomMember>,
+    problems: Vec<models::Problem>,
+    user_status: Vec<models::UserStatus>,
+}
+
+#[post("/rooms")]
+async fn create_room(
+    req: HttpRequest,
+    principal: Principal,
+    body: web::Json<CreateRoomRequest>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request to create a room");
+    principal.require(Scope::RoomAdmin)?;
+
+    // Get database client and config from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+    let config = req.app_data::<web::Data<Config>>().unwrap();
+
+    let body = body.into_inner();
+    let bands = body.bands.unwrap_or_else(|| DEFAULT_BANDS.to_vec());
+    if body.start_time >= body.end_time {
+        return Err(ApiError::BadRequest(
+            "start_time must be earlier than end_time.".to_string(),
+        ));
+    }
+    if let Err(e) = bingo::validate_bands(&bands) {
+        return Err(ApiError::BadRequest(format!("Invalid bands: {e}")));
+    }
+    if body.mode.is_team_mode() && body.teams.is_empty() {
+        return Err(ApiError::BadRequest(
+            "Some teams must be specified in this mode.".to_string(),
+        ));
+    }
+
+    let mut members: Vec<models::RoomMember> = body
+        .members
+        .into_iter()
+        .map(|user_id| models::RoomMember {
+            user_id,
+            team: None,
+        })
+        .collect();
+    for (team, user_ids) in body.teams {
+        members.extend(user_ids.into_iter().map(|user_id| models::RoomMember {
+            user_id,
+            team: Some(team.clone()),
+        }));
+    }
+    let teams: Vec<&str> = members
+        .iter()
+        .filter_map(|member| member.team.as_deref())
+        .collect();
+    for member in &members {
+        validate_user_id(&member.user_id)?;
+        if let Err(e) = body.mode.validate_team(member.team.as_deref(), &teams) {
+            return Err(ApiError::BadRequest(format!("{}: {e}", member.user_id)));
+        }
+    }
+
+    // Choose problems before storing anything so that a failure leaves no room.
+    let mut problems = get_problems().await?;
+    problems.sort_by_key(|problem| problem.difficulty);
+    let chosen_problems = bingo::choose_problems(&problems, &bands, &mut rand::thread_rng());
+    if chosen_problems.len() < bands.len() * BINGO_SIZE {
+        return Err(ApiError::BadRequest(
+            "Some bands contain too few problems.".to_string(),
+        ));
+    }
+
+    let room = models::Room {
+        id: rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(12)
+            .map(char::from)
+            .collect(),
+        name: body.name,
+        mode: body.mode,
+        bands,
+        start_time: body.start_time,
+        end_time: body.end_time,
+    };
+    let problem_entities: Vec<models::Problem> = chosen_problems
+        .iter()
+        .enumerate()
+        .map(|(position, problem)| models::Problem {
+            id: 0,
+            chosen_date: config.board_date(&room.start_time),
+            cadence: Cadence::Daily,
+            period_end: config.board_date(&room.end_time),
+            position: position as i32,
+            problem_id: problem.problem_id.clone(),
+            contest_id: problem.contest_id.clone(),
+            title: problem.title.clone(),
+            difficulty: problem.difficulty,
+            room_id: Some(room.id.clone()),
+        })
+        .collect();
+    client
+        .insert_room(&room, &members, &problem_entities)
+        .await?;
+
+    Ok(HttpResponse::Created()
+        .content_type("application/json")
+        .body(serde_json::to_string(&room).unwrap()))
+}
+
+#[post("/rooms/{room_id}/join")]
+async fn join_room(
+    req: HttpRequest,
+    room_id: web::Path<String>,
+    principal: Principal,
+    body: web::Json<JoinRoomRequest>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request to join room {room_id}");
+    principal.require(Scope::Read)?;
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    let room = match client.select_room(&room_id).await? {
+        Some(room) => room,
+        None => {
+            return Err(ApiError::NotFound(format!(
+                "Room {room_id} does not exist."
+            )))
+        }
+    };
+    let body = body.into_inner();
+    validate_user_id(&body.user_id)?;
+    // Anyone can join as themselves, and room admins can add others.
+    if body.user_id != principal.name && !principal.has_scope(Scope::RoomAdmin) {
+        return Err(ApiError::Forbidden(format!(
+            "{} cannot add {} to the room.",
+            principal.name, body.user_id
+        )));
+    }
+    // The teams are fixed when the room is created.
+    let members = client.select_room_members(&room_id).await?;
+    let teams: Vec<&str> = members
+        .iter()
+        .filter_map(|member| member.team.as_deref())
+        .collect();
+    if let Err(e) = room.mode.validate_team(body.team.as_deref(), &teams) {
+        return Err(ApiError::BadRequest(e.to_string()));
+    }
+
+    let member = models::RoomMember {
+        user_id: body.user_id,
+        team: body.team,
+    };
+    client.upsert_room_member(&room_id, &member).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/rooms/{room_id}")]
+async fn room_detail(
+    req: HttpRequest,
+    room_id: web::Path<String>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for room {room_id}");
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    let room = match client.select_room(&room_id).await? {
+        Some(room) => room,
+        None => {
+            return Err(ApiError::NotFound(format!(
+                "Room {room_id} does not exist."
+            )))
+        }
+    };
+    let members = client.select_room_members(&room_id).await?;
+    let problems = client.select_problems_by_room_id(&room_id).await?;
+    let user_status = client.select_user_status_by_room_id(&room_id).await?;
+
+    let room_view = RoomView {
+        room,
+        members,
+        problems,
+        user_status,
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&room_view).unwrap()))
+}
+
+#[get("/rooms/{room_id}/teams")]
+async fn room_teams(
+    req: HttpRequest,
+    room_id: web::Path<String>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for teams of room {room_id}");
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    let room = match client.select_room(&room_id).await? {
+        Some(room) => room,
+        None => {
+            return Err(ApiError::NotFound(format!(
+                "Room {room_id} does not exist."
+            )))
+        }
+    };
+    if !room.mode.is_team_mode() {
+        return Err(ApiError::BadRequest(format!(
+            "Room {room_id} is not played by teams."
+        )));
+    }
+
+    let members = client.select_room_members(&room_id).await?;
+    let problems = client.select_problems_by_room_id(&room_id).await?;
+    let user_status = client.select_user_status_by_room_id(&room_id).await?;
+
+    let teams: HashMap<String, String> = members
+        .into_iter()
+        .filter_map(|member| member.team.map(|team| (member.user_id, team)))
+        .collect();
+    let boards = bingo::fill_boards(&problems, &teams, &user_status, room.mode.is_claim_first());
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&boards).unwrap()))
+}
+
+#[get("/rooms/{room_id}/standings")]
+async fn room_standings(
+    req: HttpRequest,
+    room_id: web::Path<String>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for standings of room {room_id}");
+
+    // Get database client from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+
+    let room = match client.select_room(&room_id).await? {
+        Some(room) => room,
+        None => {
+            return Err(ApiError::NotFound(format!(
+                "Room {room_id} does not exist."
+            )))
+        }
+    };
+    let members = client.select_room_members(&room_id).await?;
+    let problems = client.select_problems_by_room_id(&room_id).await?;
+    let user_status = client.select_user_status_by_room_id(&room_id).await?;
+
+    // Teams compete in team modes, and members do otherwise.
+    let sides = bingo::room_sides(room.mode, &members);
+    let boards = bingo::fill_boards(&problems, &sides, &user_status, room.mode.is_claim_first());
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&bingo::standings(&boards)).unwrap()))
+}
+
+pub(super) fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_room)
+        .service(join_room)
+        .service(room_detail)
+        .service(room_teams)
+        .service(room_standings);
+}
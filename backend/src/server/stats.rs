@@ -0,0 +1,71 @@
+use super::{validate_user_id, ApiError};
+use crate::{
+    config::Config,
+    database::{models::LevelStats, DatabaseClient},
+    stats::level_stats,
+    streak::{self, StreakKind},
+};
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Serialize)]
+struct StreakView {
+    /// 0 if the streak is broken.
+    current: usize,
+    longest: usize,
+    /// The last day counted in the streak.
+    last_date: NaiveDate,
+}
+
+#[derive(Serialize)]
+struct UserStats {
+    user_id: String,
+    /// Totals on the global boards.
+    levels: Vec<LevelStats>,
+    /// Streaks which the user has ever started.
+    streaks: BTreeMap<StreakKind, StreakView>,
+}
+
+#[get("/users/{user_id}/stats")]
+async fn user_stats(
+    req: HttpRequest,
+    user_id: web::Path<String>,
+) -> actix_web::Result<impl Responder, ApiError> {
+    log::info!("Request for the stats of {user_id}");
+
+    // Get database client and config from state
+    let client = req.app_data::<web::Data<DatabaseClient>>().unwrap();
+    let config = req.app_data::<web::Data<Config>>().unwrap();
+
+    validate_user_id(&user_id)?;
+    let today = config.today();
+    let streaks = streak::select(client, &user_id)
+        .await?
+        .into_iter()
+        .map(|(kind, streak)| {
+            let view = StreakView {
+                current: streak.current_at(today),
+                longest: streak.longest,
+                last_date: streak.last_date,
+            };
+            (kind, view)
+        })
+        .collect();
+
+    let levels = level_stats(client, config, &user_id).await?;
+
+    let stats = UserStats {
+        user_id: user_id.into_inner(),
+        levels,
+        streaks,
+    };
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&stats).unwrap()))
+}
+
+pub(super) fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(user_stats);
+}
@@ -0,0 +1,106 @@
+use crate::{
+    bingo::{count_lines, Cadence, BINGO_NUM, BINGO_SIZE, LINES},
+    config::Config,
+    database::{
+        models::{LevelStats, Problem},
+        DatabaseClient,
+    },
+};
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Width of the difficulty histograms, which is the same as the colors of AtCoder.
+pub const HISTOGRAM_WIDTH: i32 = 400;
+
+/// Progress of a user on a global board.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct BoardProgress {
+    pub cadence: Cadence,
+    /// The first day of the period of the board.
+    pub chosen_date: NaiveDate,
+    /// The number of solved cells.
+    pub cells: usize,
+    /// The number of completed lines in all levels.
+    pub lines: usize,
+}
+
+/// Filled cells of each board with a solved cell, from the oldest board.
+/// `solved` are the problems solved by a user.
+pub fn filled_by_board(solved: &[Problem]) -> BTreeMap<(NaiveDate, Cadence), Vec<bool>> {
+    let mut boards: BTreeMap<(NaiveDate, Cadence), Vec<bool>> = BTreeMap::new();
+    for problem in solved {
+        let filled = boards
+            .entry((problem.chosen_date, problem.cadence))
+            .or_insert_with(|| vec![false; BINGO_NUM * BINGO_SIZE]);
+        if let Some(cell) = filled.get_mut(problem.position as usize) {
+            *cell = true;
+        }
+    }
+    boards
+}
+
+/// Progress on each board with a solved cell, from the oldest board.
+/// `solved` are the problems solved by a user.
+pub fn progress_by_board(solved: &[Problem]) -> Vec<BoardProgress> {
+    filled_by_board(solved)
+        .into_iter()
+        .map(|((chosen_date, cadence), filled)| BoardProgress {
+            cadence,
+            chosen_date,
+            cells: filled.iter().filter(|&&filled| filled).count(),
+            lines: count_lines(&filled).iter().sum(),
+        })
+        .collect()
+}
+
+/// Numbers shown in the badge of a user.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct BadgeStats {
+    /// Lines completed on today's daily board.
+    pub today: usize,
+    /// Current length of the bingo streak.
+    pub streak: usize,
+    /// Lines completed on all global boards.
+    pub lifetime: usize,
+}
+
+impl BadgeStats {
+    pub fn new(progress: &[BoardProgress], today: NaiveDate, streak: usize) -> Self {
+        Self {
+            today: progress
+                .iter()
+                .filter(|board| board.cadence == Cadence::Daily && board.chosen_date == today)
+                .map(|board| board.lines)
+                .sum(),
+            streak,
+            lifetime: progress.iter().map(|board| board.lines).sum(),
+        }
+    }
+}
+
+/// Bit mask of the cells in each line of a level, where the cell at `i` is the `i`-th bit.
+pub fn line_masks() -> Vec<i32> {
+    LINES
+        .iter()
+        .map(|line| line.iter().map(|&cell| 1 << cell).sum())
+        .collect()
+}
+
+/// Totals of `user_id` in each level of the global boards.
+pub async fn level_stats(
+    client: &DatabaseClient,
+    config: &Config,
+    user_id: &str,
+) -> Result<Vec<LevelStats>> {
+    client
+        .select_level_stats(
+            user_id,
+            config.timezone.name(),
+            BINGO_SIZE as i32,
+            &line_masks(),
+            HISTOGRAM_WIDTH,
+        )
+        .await
+}
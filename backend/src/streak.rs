@@ -0,0 +1,243 @@
+use crate::{
+    bingo::{count_lines, BoardPeriod, Cadence, BINGO_SIZE},
+    database::{
+        models::{Problem, UserStreak},
+        DatabaseClient,
+    },
+    stats::filled_by_board,
+};
+use anyhow::{anyhow, Error, Result};
+use chrono::{Duration, NaiveDate};
+use serde::{Serialize, Serializer};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+/// What a user has to do on the daily board every day to keep a streak.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StreakKind {
+    /// Solve a cell.
+    Solved,
+    /// Complete a line.
+    Bingo,
+    /// Fill all cells in the level (0-indexed).
+    FullLevel(usize),
+}
+
+impl fmt::Display for StreakKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreakKind::Solved => f.write_str("solved"),
+            StreakKind::Bingo => f.write_str("bingo"),
+            StreakKind::FullLevel(level) => write!(f, "full_level_{}", level + 1),
+        }
+    }
+}
+
+impl FromStr for StreakKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "solved" => Ok(StreakKind::Solved),
+            "bingo" => Ok(StreakKind::Bingo),
+            _ => s
+                .strip_prefix("full_level_")
+                .and_then(|level| level.parse::<usize>().ok())
+                .filter(|&level| level > 0)
+                .map(|level| StreakKind::FullLevel(level - 1))
+                .ok_or_else(|| anyhow!("Unknown streak kind: {s:?}")),
+        }
+    }
+}
+
+impl Serialize for StreakKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Consecutive days on which a user did something.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Streak {
+    /// Length of the streak ending on `last_date`.
+    pub current: usize,
+    pub longest: usize,
+    pub last_date: NaiveDate,
+}
+
+impl Streak {
+    pub fn start(date: NaiveDate) -> Self {
+        Self {
+            current: 1,
+            longest: 1,
+            last_date: date,
+        }
+    }
+
+    /// Count `date` in the streak.
+    /// Return `false` without changes if `date` is before `last_date`,
+    /// in which case the streak has to be rebuilt from the history.
+    pub fn extend(&mut self, date: NaiveDate) -> bool {
+        if date < self.last_date {
+            return false;
+        }
+        if date == self.last_date + Duration::days(1) {
+            self.current += 1;
+        } else if date > self.last_date {
+            self.current = 1;
+        }
+        self.last_date = date;
+        self.longest = self.longest.max(self.current);
+        true
+    }
+
+    /// Length of the streak as of `today`.
+    /// The streak is kept until today ends even if today is not counted yet.
+    pub fn current_at(&self, today: NaiveDate) -> usize {
+        if self.last_date >= today - Duration::days(1) {
+            self.current
+        } else {
+            0
+        }
+    }
+}
+
+impl From<&UserStreak> for Streak {
+    fn from(streak: &UserStreak) -> Self {
+        Self {
+            current: streak.current as usize,
+            longest: streak.longest as usize,
+            last_date: streak.last_date,
+        }
+    }
+}
+
+fn to_model(kind: StreakKind, streak: &Streak) -> UserStreak {
+    UserStreak {
+        kind: kind.to_string(),
+        current: streak.current as i32,
+        longest: streak.longest as i32,
+        last_date: streak.last_date,
+    }
+}
+
+/// Kinds of the streaks which a daily board with the `filled` cells counts for.
+pub fn qualified_kinds(filled: &[bool]) -> Vec<StreakKind> {
+    let mut kinds = Vec::new();
+    if filled.iter().any(|&filled| filled) {
+        kinds.push(StreakKind::Solved);
+    }
+    if count_lines(filled).iter().any(|&lines| lines > 0) {
+        kinds.push(StreakKind::Bingo);
+    }
+    for (level, cells) in filled.chunks(BINGO_SIZE).enumerate() {
+        if cells.iter().all(|&filled| filled) {
+            kinds.push(StreakKind::FullLevel(level));
+        }
+    }
+    kinds
+}
+
+/// Streaks from the filled cells of each daily board, which are ordered by the dates.
+pub fn compute(boards: &[(NaiveDate, Vec<bool>)]) -> BTreeMap<StreakKind, Streak> {
+    let mut streaks: BTreeMap<StreakKind, Streak> = BTreeMap::new();
+    for (date, filled) in boards {
+        for kind in qualified_kinds(filled) {
+            streaks
+                .entry(kind)
+                .and_modify(|streak| {
+                    streak.extend(*date);
+                })
+                .or_insert_with(|| Streak::start(*date));
+        }
+    }
+    streaks
+}
+
+/// Stored streaks of `user_id` by their kinds.
+pub async fn select(
+    client: &DatabaseClient,
+    user_id: &str,
+) -> Result<BTreeMap<StreakKind, Streak>> {
+    let mut streaks = BTreeMap::new();
+    for streak in client.select_user_streaks(user_id).await? {
+        streaks.insert(streak.kind.parse()?, Streak::from(&streak));
+    }
+    Ok(streaks)
+}
+
+/// Streaks to store for a user from all of the global problems which they solved.
+pub fn from_solved(solved: &[Problem]) -> Vec<UserStreak> {
+    let boards: Vec<(NaiveDate, Vec<bool>)> = filled_by_board(solved)
+        .into_iter()
+        .filter(|((_, cadence), _)| *cadence == Cadence::Daily)
+        .map(|((date, _), filled)| (date, filled))
+        .collect();
+    compute(&boards)
+        .iter()
+        .map(|(kind, streak)| to_model(*kind, streak))
+        .collect()
+}
+
+/// Calculate the streaks of `user_id` from all of their solves, and store them.
+pub async fn rebuild(client: &DatabaseClient, user_id: &str) -> Result<()> {
+    let solved = client.select_solved_global_problems(user_id).await?;
+    client
+        .replace_user_streaks(user_id, &from_solved(&solved))
+        .await
+}
+
+/// Rebuild the streaks of all users.
+pub async fn rebuild_all(client: &DatabaseClient) -> Result<()> {
+    let user_ids = client.select_users_with_global_ac().await?;
+    for user_id in &user_ids {
+        rebuild(client, user_id).await?;
+    }
+    log::info!("Rebuilt the streaks of {} users.", user_ids.len());
+    Ok(())
+}
+
+/// Count the board of `problem`, where `user_id` has just solved a cell, in their streaks.
+/// Only the daily global boards are counted.
+pub async fn record_solve(client: &DatabaseClient, user_id: &str, problem: &Problem) -> Result<()> {
+    if problem.cadence != Cadence::Daily || problem.room_id.is_some() {
+        return Ok(());
+    }
+
+    let period = BoardPeriod {
+        cadence: problem.cadence,
+        start: problem.chosen_date,
+        end: problem.period_end,
+    };
+    let problems = client.select_problems_by_period(&period).await?;
+    let problem_row_ids: Vec<i32> = problems.iter().map(|problem| problem.id).collect();
+    let user_status = client
+        .select_user_status_by_user_id_and_problem_row_ids(user_id, &problem_row_ids)
+        .await?;
+    let mut filled = vec![false; problems.len()];
+    for problem in &problems {
+        let accepted = user_status
+            .iter()
+            .any(|status| status.problem_row_id == problem.id && status.accepted);
+        if let Some(cell) = filled.get_mut(problem.position as usize) {
+            *cell = accepted;
+        }
+    }
+
+    let mut streaks = select(client, user_id).await?;
+    for kind in qualified_kinds(&filled) {
+        let streak = match streaks.get_mut(&kind) {
+            Some(streak) => {
+                // A solve on an older board, e.g. by a backfill, changes the history.
+                if !streak.extend(problem.chosen_date) {
+                    return rebuild(client, user_id).await;
+                }
+                *streak
+            }
+            None => Streak::start(problem.chosen_date),
+        };
+        client
+            .upsert_user_streak(user_id, &to_model(kind, &streak))
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use std::{future::Future, time::Duration};
+use tokio::time::{sleep, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// Delays between restarts of a failing worker.
+/// The delay doubles on every failure, and is reset after the worker runs long enough.
+#[derive(Clone, Debug)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1), Duration::from_secs(300))
+    }
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            current: initial,
+        }
+    }
+
+    /// Return the delay before the next restart, and double the following one.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = (self.current * 2).min(self.max);
+        delay
+    }
+
+    pub fn reset(&mut self) {
+        self.current = self.initial;
+    }
+}
+
+/// A worker which has run this long is regarded as healthy.
+const HEALTHY_DURATION: Duration = Duration::from_secs(600);
+
+/// Run the worker made by `make_worker` until `token` is cancelled.
+/// Restart it with `backoff` whenever it panics or stops before the cancellation.
+///
+/// The worker receives `token` and is expected to return soon after it is cancelled,
+/// so that an in-flight job is never interrupted.
+pub async fn supervise<F, Fut>(
+    name: &str,
+    token: CancellationToken,
+    mut backoff: Backoff,
+    mut make_worker: F,
+) where
+    F: FnMut(CancellationToken) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    loop {
+        let started_at = Instant::now();
+        let result = tokio::spawn(make_worker(token.clone())).await;
+        if token.is_cancelled() {
+            log::info!("The {name} stopped.");
+            return;
+        }
+
+        match result {
+            Ok(()) => log::error!("The {name} stopped unexpectedly."),
+            Err(e) => log::error!("The {name} panicked: {e}"),
+        }
+        if started_at.elapsed() >= HEALTHY_DURATION {
+            backoff.reset();
+        }
+        let delay = backoff.next_delay();
+        log::info!("Restart the {name} in {} secs.", delay.as_secs());
+
+        tokio::select! {
+            _ = sleep(delay) => {}
+            _ = token.cancelled() => {
+                log::info!("The {name} stopped.");
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use crate::{
+    bingo::{self, BoardPeriod, BINGO_SIZE},
+    config::Config,
+    crawler::submissions::{get_recent_submissions, Submission},
+    database::{
+        models::{self, UserStatus},
+        DatabaseClient,
+    },
+    events::{self, BoardKey, Event},
+    metrics::metrics,
+    streak,
+    webhook::{self, Notification},
+};
+use anyhow::{Context, Result};
+use std::time::Duration;
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+
+/// How a submission changes the status of a cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StatusChange {
+    Unchanged,
+    Updated,
+    /// The cell gets the first AC.
+    Solved,
+}
+
+/// Insert or update the status of the submitter for the problem if necessary.
+async fn apply_user_status(
+    client: &DatabaseClient,
+    submission: &Submission,
+    problem_row_id: i32,
+) -> Result<StatusChange> {
+    let new_user_status = UserStatus {
+        user_id: submission.user_id.clone(),
+        problem_row_id,
+        accepted: submission.is_accepted,
+        accepted_time: submission.is_accepted.then_some(submission.submission_time),
+        submission_id: submission.is_accepted.then_some(submission.id as i64),
+    };
+
+    // Search the current user status.
+    let old_user_status_opt = client
+        .select_user_status(&new_user_status.user_id, new_user_status.problem_row_id)
+        .await?;
+
+    // Insert or update the user status if necessary.
+    // Keep the earliest AC so that the first claim of a cell is determined.
+    match old_user_status_opt {
+        Some(old_user_status) => {
+            if new_user_status.accepted_before(&old_user_status) {
+                client.update_user_status(&new_user_status).await?;
+                metrics()
+                    .user_status_writes
+                    .with_label_values(&["update"])
+                    .inc();
+                if old_user_status.accepted {
+                    Ok(StatusChange::Updated)
+                } else {
+                    Ok(StatusChange::Solved)
+                }
+            } else {
+                Ok(StatusChange::Unchanged)
+            }
+        }
+        None => {
+            if !client.insert_user_status(&new_user_status).await? {
+                return Ok(StatusChange::Unchanged);
+            }
+            metrics()
+                .user_status_writes
+                .with_label_values(&["insert"])
+                .inc();
+            if new_user_status.accepted {
+                Ok(StatusChange::Solved)
+            } else {
+                Ok(StatusChange::Updated)
+            }
+        }
+    }
+}
+
+/// Publish that `submission` solved `problem`, and the lines completed by it.
+/// The webhooks are notified of the lines.
+async fn publish_progress(
+    client: &DatabaseClient,
+    submission: &Submission,
+    problem: &models::Problem,
+) -> Result<()> {
+    let board = BoardKey {
+        cadence: problem.cadence,
+        chosen_date: problem.chosen_date,
+        room_id: problem.room_id.clone(),
+    };
+    let event = Event::CellSolved {
+        user_id: submission.user_id.clone(),
+        board: board.clone(),
+        position: problem.position,
+        problem_id: problem.problem_id.clone(),
+        accepted_time: submission.submission_time,
+    };
+    events::publish(client, &event).await?;
+
+    // Fill the board of the submitter.
+    let filled = match &problem.room_id {
+        // Cells claimed by other sides are not filled in claim-first rooms.
+        Some(room_id) => {
+            let room = client
+                .select_room(room_id)
+                .await?
+                .with_context(|| format!("Room {room_id} does not exist."))?;
+            let members = client.select_room_members(room_id).await?;
+            let problems = client.select_problems_by_room_id(room_id).await?;
+            let user_status = client.select_user_status_by_room_id(room_id).await?;
+            bingo::filled_by_side_of(
+                room.mode,
+                &members,
+                &problems,
+                &user_status,
+                &submission.user_id,
+            )
+        }
+        None => {
+            let period = BoardPeriod {
+                cadence: problem.cadence,
+                start: problem.chosen_date,
+                end: problem.period_end,
+            };
+            let problems = client.select_problems_by_period(&period).await?;
+            let problem_row_ids: Vec<i32> = problems.iter().map(|problem| problem.id).collect();
+            let user_status = client
+                .select_user_status_by_user_id_and_problem_row_ids(
+                    &submission.user_id,
+                    &problem_row_ids,
+                )
+                .await?;
+            let mut filled = vec![false; problems.len()];
+            for problem in &problems {
+                let accepted = user_status
+                    .iter()
+                    .any(|status| status.problem_row_id == problem.id && status.accepted);
+                if let Some(cell) = filled.get_mut(problem.position as usize) {
+                    *cell = accepted;
+                }
+            }
+            filled
+        }
+    };
+
+    let position = problem.position as usize;
+    if bingo::count_lines_through(&filled, position) > 0 {
+        let level = position / BINGO_SIZE;
+        let lines = bingo::count_lines(&filled)[level];
+        let full_board = filled.iter().all(|&cell| cell);
+        let event = Event::BingoAchieved {
+            user_id: submission.user_id.clone(),
+            board: board.clone(),
+            level,
+            lines,
+            full_board,
+        };
+        events::publish(client, &event).await?;
+
+        let notification = if full_board {
+            Notification::FullBoard {
+                user_id: submission.user_id.clone(),
+                board,
+            }
+        } else {
+            Notification::LineCompleted {
+                user_id: submission.user_id.clone(),
+                board,
+                level,
+                lines,
+            }
+        };
+        webhook::enqueue(client, &notification).await?;
+    }
+    Ok(())
+}
+
+/// Apply the submission to the corresponding boards.
+/// The progress is published only if `notify` is set, so that old solves can be applied quietly.
+/// Return whether or not any status is changed.
+pub async fn update_user_status(
+    client: &DatabaseClient,
+    config: &Config,
+    submission: &Submission,
+    notify: bool,
+) -> Result<bool> {
+    // Search the corresponding problems in the global boards and the rooms.
+    let submission_date = config.board_date(&submission.submission_time);
+
+    let mut problems = client
+        .select_problems_by_date_and_id(&submission_date, &submission.problem_id)
+        .await?;
+    problems.append(
+        &mut client
+            .select_problems_in_open_rooms_of_user(
+                &submission.user_id,
+                &submission.problem_id,
+                &submission.submission_time,
+            )
+            .await?,
+    );
+
+    let mut updated = false;
+    for problem in problems {
+        match apply_user_status(client, submission, problem.id).await? {
+            StatusChange::Unchanged => {}
+            StatusChange::Updated => updated = true,
+            StatusChange::Solved => {
+                updated = true;
+                if notify {
+                    if let Err(e) = publish_progress(client, submission, &problem).await {
+                        log::error!("Failed to publish the progress: {e}");
+                    }
+                }
+                if let Err(e) = streak::record_solve(client, &submission.user_id, &problem).await {
+                    log::error!(
+                        "Failed to update the streaks of {}: {e}",
+                        submission.user_id
+                    );
+                }
+            }
+        }
+    }
+    Ok(updated)
+}
+
+/// Apply submissions in these 60 minutes in every 30 secs until `token` is cancelled.
+/// Fetched submissions are applied before returning.
+pub async fn run(client: &DatabaseClient, config: &Config, token: &CancellationToken) {
+    while !token.is_cancelled() {
+        match get_recent_submissions(60).await {
+            Ok(submissions) => {
+                for submission in submissions {
+                    if let Err(e) = update_user_status(client, config, &submission, true).await {
+                        log::error!("Failed to update user status: {e}");
+                    }
+                }
+                log::info!("Finished to update user status.");
+            }
+            Err(e) => log::error!("Failed to fetch recent submissions: {e}"),
+        }
+        tokio::select! {
+            _ = sleep(Duration::from_secs(30)) => {}
+            _ = token.cancelled() => {}
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use crate::{
+    crawler::problems::Problem,
+    database::{
+        models::{task_url, Webhook, WebhookDelivery, WebhookFormat},
+        DatabaseClient,
+    },
+    events::BoardKey,
+};
+use anyhow::{anyhow, bail, Result};
+use chrono::{Duration, NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Sha256;
+use std::net::{IpAddr, SocketAddr};
+use tokio::net::lookup_host;
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+
+/// Header with the HMAC-SHA256 of the body keyed by the secret of the webhook.
+pub const SIGNATURE_HEADER: &str = "X-Bingo-Signature";
+
+/// Interval to look for the deliveries to be sent.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// The maximum number of the deliveries sent in a poll.
+const BATCH_SIZE: i64 = 20;
+
+/// Time for which a delivery being sent is hidden from other workers.
+const LEASE_SECS: f64 = 60.0;
+
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Deliveries are given up after this number of the failed attempts.
+pub const MAX_ATTEMPTS: i32 = 12;
+
+/// Problem on a new board.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardProblem {
+    pub problem_id: String,
+    pub contest_id: String,
+    pub title: String,
+    pub difficulty: i32,
+}
+
+impl From<&Problem> for BoardProblem {
+    fn from(problem: &Problem) -> Self {
+        Self {
+            problem_id: problem.problem_id.clone(),
+            contest_id: problem.contest_id.clone(),
+            title: problem.title.clone(),
+            difficulty: problem.difficulty,
+        }
+    }
+}
+
+impl BoardProblem {
+    fn url(&self) -> String {
+        task_url(&self.contest_id, &self.problem_id)
+    }
+}
+
+/// Notification sent to webhooks.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Notification {
+    BoardCreated {
+        board: BoardKey,
+        /// The last day of the period of the board (inclusive).
+        period_end: NaiveDate,
+        problems: Vec<BoardProblem>,
+    },
+    /// The user completed a line in a level, but not the full board.
+    LineCompleted {
+        user_id: String,
+        board: BoardKey,
+        level: usize,
+        /// The number of completed lines in the level.
+        lines: usize,
+    },
+    FullBoard {
+        user_id: String,
+        board: BoardKey,
+    },
+}
+
+fn describe_board(board: &BoardKey) -> String {
+    match &board.room_id {
+        Some(room_id) => format!("the board of room {room_id}"),
+        None => format!("the {} board from {}", board.cadence, board.chosen_date),
+    }
+}
+
+impl Notification {
+    pub fn board(&self) -> &BoardKey {
+        match self {
+            Notification::BoardCreated { board, .. }
+            | Notification::LineCompleted { board, .. }
+            | Notification::FullBoard { board, .. } => board,
+        }
+    }
+
+    /// Message for chat services.
+    pub fn message(&self) -> String {
+        match self {
+            Notification::BoardCreated {
+                board,
+                period_end,
+                problems,
+            } => {
+                let mut message = format!(
+                    "New {} board from {} to {}:",
+                    board.cadence, board.chosen_date, period_end
+                );
+                for problem in problems {
+                    message += &format!(
+                        "\n- {} ({}) {}",
+                        problem.title,
+                        problem.difficulty,
+                        problem.url()
+                    );
+                }
+                message
+            }
+            Notification::LineCompleted {
+                user_id,
+                board,
+                level,
+                lines,
+            } => format!(
+                "{user_id} completed a line in level {} of {} ({lines} lines).",
+                level + 1,
+                describe_board(board)
+            ),
+            Notification::FullBoard { user_id, board } => {
+                format!("{user_id} filled all cells of {}!", describe_board(board))
+            }
+        }
+    }
+}
+
+/// Body of the request to a webhook in `format`.
+pub fn render(format: WebhookFormat, notification: &Notification) -> String {
+    let body = match format {
+        WebhookFormat::Json => serde_json::to_value(notification).unwrap(),
+        WebhookFormat::Discord => json!({ "content": notification.message() }),
+        WebhookFormat::Slack => json!({ "text": notification.message() }),
+    };
+    body.to_string()
+}
+
+/// Value of `SIGNATURE_HEADER` for `body`.
+pub fn sign(secret: &str, body: &str) -> String {
+    // HMAC accepts keys of any length.
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(body.as_bytes());
+    format!("sha256={:x}", mac.finalize().into_bytes())
+}
+
+/// Delay before the next attempt after `attempts` failed ones.
+/// Return `None` if the delivery should be given up.
+pub fn retry_delay(attempts: i32) -> Option<Duration> {
+    if attempts >= MAX_ATTEMPTS {
+        return None;
+    }
+    // 30 seconds, 1 minute, 2 minutes, ..., up to 6 hours.
+    let delay = Duration::seconds(30) * 2i32.pow(attempts.clamp(1, 20) as u32 - 1);
+    Some(delay.min(Duration::hours(6)))
+}
+
+/// Whether `ip` is on the public internet rather than next to the server.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            // 0.0.0.0/8 is "this network".
+            let this_network = a == 0;
+            // 100.64.0.0/10 is shared by the carrier-grade NATs.
+            let shared = a == 100 && (64..128).contains(&b);
+            // 198.18.0.0/15 is for benchmarking.
+            let benchmarking = a == 198 && b & 0xfe == 18;
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || this_network
+                || shared
+                || benchmarking)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(ip.into()),
+            None => {
+                let first = ip.segments()[0];
+                // fc00::/7 is for the unique local addresses, and fe80::/10 for the link-local.
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Fail unless `url` is an HTTP(S) URL whose host resolves only to public addresses,
+/// so that webhooks cannot be used to reach the internal network.
+/// Return the host and the address to connect to.
+pub async fn check_url(url: &str) -> Result<(String, SocketAddr)> {
+    let url = reqwest::Url::parse(url)?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        bail!("The scheme must be http or https.");
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| anyhow!("The host is missing."))?;
+    // IPv6 hosts are enclosed in brackets in URLs.
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addrs: Vec<_> = lookup_host((host, port)).await?.collect();
+    if addrs.is_empty() {
+        bail!("{host} is not resolved.");
+    }
+    if let Some(addr) = addrs.iter().find(|addr| !is_public_ip(addr.ip())) {
+        bail!("{host} is resolved to a non-public address {}.", addr.ip());
+    }
+    Ok((host.to_string(), addrs[0]))
+}
+
+/// Client which connects to `addr` for `host` and does not follow redirects,
+/// so that neither a DNS change after `check_url` nor a redirect reaches the internal network.
+pub fn http_client(host: &str, addr: SocketAddr) -> Result<reqwest::Client> {
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve(host, addr)
+        .build()?;
+    Ok(client)
+}
+
+/// Queue `notification` for the webhooks of its board.
+pub async fn enqueue(client: &DatabaseClient, notification: &Notification) -> Result<()> {
+    let room_id = notification.board().room_id.as_deref();
+    let payload = serde_json::to_value(notification)?;
+    client.enqueue_webhook_deliveries(room_id, &payload).await?;
+    Ok(())
+}
+
+/// Send `notification` to `webhook`, and fail unless it responds with a success.
+pub async fn send(
+    http: &reqwest::Client,
+    webhook: &Webhook,
+    notification: &Notification,
+) -> Result<()> {
+    let body = render(webhook.format, notification);
+    let response = http
+        .post(&webhook.url)
+        .header("Content-Type", "application/json")
+        .header(SIGNATURE_HEADER, sign(&webhook.secret, &body))
+        .timeout(REQUEST_TIMEOUT)
+        .body(body)
+        .send()
+        .await?
+        .error_for_status()?;
+    if !response.status().is_success() {
+        bail!("The webhook responded with {}.", response.status());
+    }
+    Ok(())
+}
+
+async fn deliver(client: &DatabaseClient, delivery: &WebhookDelivery) {
+    // The host is checked again since it may be resolved differently from the creation.
+    let http = check_url(&delivery.webhook.url)
+        .await
+        .and_then(|(host, addr)| http_client(&host, addr));
+    let result = match (serde_json::from_value(delivery.payload.clone()), http) {
+        (Ok(notification), Ok(http)) => send(&http, &delivery.webhook, &notification).await,
+        (Err(e), _) => Err(e.into()),
+        (_, Err(e)) => Err(e),
+    };
+    let marked = match result {
+        Ok(()) => client.mark_webhook_delivered(delivery.id).await,
+        Err(e) => {
+            let next_attempt_at =
+                retry_delay(delivery.attempts + 1).map(|delay| Utc::now() + delay);
+            match next_attempt_at {
+                Some(time) => log::warn!(
+                    "Failed to deliver {} to webhook {}, which is retried at {time}: {e}",
+                    delivery.id,
+                    delivery.webhook.id
+                ),
+                None => log::error!(
+                    "Gave up delivering {} to webhook {}: {e}",
+                    delivery.id,
+                    delivery.webhook.id
+                ),
+            }
+            client
+                .mark_webhook_failed(delivery.id, &e.to_string(), next_attempt_at)
+                .await
+        }
+    };
+    if let Err(e) = marked {
+        log::error!("Failed to record the delivery {}: {e}", delivery.id);
+    }
+}
+
+/// Send the queued deliveries until `token` is cancelled.
+/// A delivery being sent is finished before returning.
+pub async fn run(client: &DatabaseClient, token: &CancellationToken) {
+    while !token.is_cancelled() {
+        match client
+            .take_due_webhook_deliveries(BATCH_SIZE, LEASE_SECS)
+            .await
+        {
+            Ok(deliveries) => {
+                for delivery in &deliveries {
+                    deliver(client, delivery).await;
+                }
+                // Look for more at once if the batch is full.
+                if deliveries.len() as i64 == BATCH_SIZE {
+                    continue;
+                }
+            }
+            Err(e) => log::error!("Failed to take webhook deliveries: {e}"),
+        }
+
+        tokio::select! {
+            _ = sleep(POLL_INTERVAL) => {}
+            _ = token.cancelled() => {}
+        }
+    }
+}
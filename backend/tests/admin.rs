@@ -0,0 +1,35 @@
+use atcoder_bingo_backend::{
+    bingo::{choose_replacement, Band},
+    crawler::problems::Problem,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+fn problem(problem_id: &str, difficulty: i32) -> Problem {
+    Problem {
+        problem_id: problem_id.to_string(),
+        contest_id: "abc250".to_string(),
+        title: problem_id.to_string(),
+        difficulty,
+    }
+}
+
+#[test]
+fn replacement_is_in_the_band_and_not_on_the_board() {
+    let problems = vec![
+        problem("abc250_a", 100),
+        problem("abc250_b", 450),
+        problem("abc250_c", 500),
+        problem("abc250_d", 1500),
+    ];
+    let band = Band {
+        lower: 400,
+        upper: 1400,
+    };
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for _ in 0..10 {
+        let replacement = choose_replacement(&problems, &band, &["abc250_b"], &mut rng);
+        assert_eq!(replacement.unwrap().problem_id, "abc250_c");
+    }
+    assert!(choose_replacement(&problems, &band, &["abc250_b", "abc250_c"], &mut rng).is_none());
+}
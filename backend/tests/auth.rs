@@ -0,0 +1,61 @@
+use atcoder_bingo_backend::{
+    auth::{hash_token, Principal, Scope},
+    config::{Config, StaticToken},
+};
+
+#[test]
+fn wider_scopes_include_narrower_ones() {
+    let principal = Principal {
+        name: "alice".to_string(),
+        scopes: vec![Scope::RoomAdmin],
+    };
+    assert!(principal.has_scope(Scope::Read));
+    assert!(principal.has_scope(Scope::RoomAdmin));
+    assert!(!principal.has_scope(Scope::GlobalAdmin));
+}
+
+#[test]
+fn static_tokens_are_looked_up_by_the_token() {
+    let config = Config {
+        static_tokens: vec![
+            "alice:global-admin:secret".parse().unwrap(),
+            "bob:read+room-admin:another:secret".parse().unwrap(),
+        ],
+        ..Config::default()
+    };
+
+    assert_eq!(
+        config.static_principal("secret"),
+        Some(Principal {
+            name: "alice".to_string(),
+            scopes: vec![Scope::GlobalAdmin],
+        })
+    );
+    assert_eq!(
+        config.static_principal("another:secret"),
+        Some(Principal {
+            name: "bob".to_string(),
+            scopes: vec![Scope::Read, Scope::RoomAdmin],
+        })
+    );
+    assert_eq!(config.static_principal("alice"), None);
+    assert!(!format!("{config:?}").contains("secret"));
+    // Only the hash is kept.
+    assert_eq!(config.static_tokens[0].token_hash, hash_token("secret"));
+}
+
+#[test]
+fn malformed_static_tokens_are_rejected() {
+    assert!("alice:secret".parse::<StaticToken>().is_err());
+    assert!(":read:secret".parse::<StaticToken>().is_err());
+    assert!("alice:read:".parse::<StaticToken>().is_err());
+    assert!("alice:owner:secret".parse::<StaticToken>().is_err());
+}
+
+#[test]
+fn tokens_are_hashed_with_sha256() {
+    assert_eq!(
+        hash_token("secret"),
+        "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b"
+    );
+}
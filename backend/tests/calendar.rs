@@ -0,0 +1,68 @@
+mod common;
+
+use atcoder_bingo_backend::{
+    bingo::Cadence,
+    calendar::{render_ics, CalendarEvent},
+    config::Config,
+    database::models::Problem,
+};
+use chrono::{TimeZone, Utc};
+use common::problem;
+
+fn render(problems: Vec<Problem>) -> String {
+    let event = CalendarEvent {
+        uid: "daily-2022-05-01@atcoder-bingo".to_string(),
+        summary: "Bingo; with friends, and more".to_string(),
+        start: Utc.with_ymd_and_hms(2022, 4, 30, 15, 0, 0).unwrap(),
+        end: Utc.with_ymd_and_hms(2022, 5, 1, 15, 0, 0).unwrap(),
+        url: "http://localhost/atcoder-bingo-api/boards/daily/2022-05-01".to_string(),
+        problems,
+    };
+    let stamp = Utc.with_ymd_and_hms(2022, 4, 30, 0, 0, 0).unwrap();
+    render_ics("AtCoder Bingo", &[event], &stamp)
+}
+
+#[test]
+fn event_has_the_period_in_utc() {
+    let ics = render(Vec::new());
+
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+    assert!(ics.contains("\r\nDTSTART:20220430T150000Z\r\n"));
+    assert!(ics.contains("\r\nDTEND:20220501T150000Z\r\n"));
+    assert!(ics.contains("\r\nSUMMARY:Bingo\\; with friends\\, and more\r\n"));
+}
+
+#[test]
+fn long_lines_are_folded() {
+    let ics = render(
+        (0..18)
+            .map(|position| problem("2022-05-01", position))
+            .collect(),
+    );
+
+    for line in ics.split("\r\n") {
+        assert!(line.len() <= 75, "{line:?} is too long.");
+    }
+    // Unfolding restores the description.
+    let unfolded = ics.replace("\r\n ", "");
+    assert!(unfolded.contains(
+        "\\n\\nLevel 2\\n- Problem 9 (100) https://atcoder.jp/contests/abc250/tasks/abc250_9\\n"
+    ));
+}
+
+#[test]
+fn events_link_to_the_frontend_pages() {
+    let config = Config {
+        frontend_url: "https://bingo.example".to_string(),
+        ..Config::default()
+    };
+    assert_eq!(
+        config.board_page_url(Cadence::Weekly, &"2022-05-02".parse().unwrap()),
+        "https://bingo.example/?cadence=weekly&date=2022-05-02"
+    );
+    assert_eq!(
+        config.room_page_url("abc123"),
+        "https://bingo.example/?room=abc123"
+    );
+}
@@ -0,0 +1,30 @@
+//! Helpers shared by the integration tests.
+//! Each test crate uses only some of them.
+#![allow(dead_code)]
+
+use atcoder_bingo_backend::{bingo::Cadence, database::models::Problem};
+use chrono::{DateTime, NaiveDate, Utc};
+
+pub fn date(date: &str) -> NaiveDate {
+    date.parse().unwrap()
+}
+
+pub fn utc(time: &str) -> DateTime<Utc> {
+    time.parse().unwrap()
+}
+
+/// A problem at `position` of the daily board of `chosen_date`.
+pub fn problem(chosen_date: &str, position: i32) -> Problem {
+    Problem {
+        id: position + 1,
+        chosen_date: date(chosen_date),
+        cadence: Cadence::Daily,
+        period_end: date(chosen_date),
+        position,
+        problem_id: format!("abc250_{position}"),
+        contest_id: "abc250".to_string(),
+        title: format!("Problem {position}"),
+        difficulty: 100,
+        room_id: None,
+    }
+}
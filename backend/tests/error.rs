@@ -0,0 +1,46 @@
+use actix_web::{http::StatusCode, ResponseError};
+use anyhow::anyhow;
+use atcoder_bingo_backend::server::ApiError;
+use deadpool_postgres::{PoolError, TimeoutType};
+use tokio_postgres::NoTls;
+
+fn status(e: anyhow::Error) -> StatusCode {
+    ApiError::from(e).status_code()
+}
+
+#[tokio::test]
+async fn failed_connections_are_unavailable() {
+    // Nothing listens on the port.
+    let db_error = tokio_postgres::connect("host=127.0.0.1 port=1 user=postgres", NoTls)
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(status(db_error.into()), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn pool_errors_are_unavailable() {
+    let db_error = tokio_postgres::connect("host=127.0.0.1 port=1 user=postgres", NoTls)
+        .await
+        .err()
+        .unwrap();
+    for pool_error in [
+        PoolError::Backend(db_error),
+        PoolError::Timeout(TimeoutType::Wait),
+        PoolError::Closed,
+    ] {
+        assert_eq!(status(pool_error.into()), StatusCode::SERVICE_UNAVAILABLE);
+    }
+    assert_eq!(
+        status(PoolError::NoRuntimeSpecified.into()),
+        StatusCode::INTERNAL_SERVER_ERROR
+    );
+}
+
+#[test]
+fn other_errors_are_internal() {
+    assert_eq!(
+        status(anyhow!("Something went wrong.")),
+        StatusCode::INTERNAL_SERVER_ERROR
+    );
+}
@@ -0,0 +1,77 @@
+use atcoder_bingo_backend::{
+    bingo::{count_lines_through, Cadence},
+    events::{BoardKey, Event, EventBus},
+};
+use serde_json::json;
+use tokio_util::sync::CancellationToken;
+
+fn board() -> BoardKey {
+    BoardKey {
+        cadence: Cadence::Daily,
+        chosen_date: "2022-05-01".parse().unwrap(),
+        room_id: None,
+    }
+}
+
+#[test]
+fn only_lines_through_the_cell_are_counted() {
+    // The first level has the top row and the left column.
+    let mut filled = vec![false; 18];
+    for cell in [0, 1, 2, 3, 6] {
+        filled[cell] = true;
+    }
+
+    assert_eq!(count_lines_through(&filled, 0), 2);
+    assert_eq!(count_lines_through(&filled, 1), 1);
+    assert_eq!(count_lines_through(&filled, 6), 1);
+    assert_eq!(count_lines_through(&filled, 4), 0);
+
+    // The second level is independent of the first one.
+    filled[9] = true;
+    assert_eq!(count_lines_through(&filled, 9), 0);
+}
+
+#[test]
+fn events_are_tagged_with_their_type() {
+    let event = Event::BingoAchieved {
+        user_id: "tourist".to_string(),
+        board: board(),
+        level: 2,
+        lines: 1,
+        full_board: false,
+    };
+
+    assert_eq!(event.name(), "bingo_achieved");
+    assert_eq!(
+        serde_json::to_value(&event).unwrap(),
+        json!({
+            "type": "bingo_achieved",
+            "user_id": "tourist",
+            "board": { "cadence": "daily", "chosen_date": "2022-05-01", "room_id": null },
+            "level": 2,
+            "lines": 1,
+            "full_board": false,
+        })
+    );
+    let parsed: Event = serde_json::from_value(serde_json::to_value(&event).unwrap()).unwrap();
+    assert_eq!(parsed, event);
+}
+
+#[tokio::test]
+async fn every_subscriber_receives_events() {
+    let bus = EventBus::new(CancellationToken::new());
+    let mut first = bus.subscribe();
+    let mut second = bus.clone().subscribe();
+
+    let event = Event::CellSolved {
+        user_id: "tourist".to_string(),
+        board: board(),
+        position: 4,
+        problem_id: "abc250_a".to_string(),
+        accepted_time: "2022-05-01T03:00:00Z".parse().unwrap(),
+    };
+    bus.send(event.clone());
+
+    assert_eq!(first.recv().await.unwrap(), event);
+    assert_eq!(second.recv().await.unwrap(), event);
+}
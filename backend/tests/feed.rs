@@ -0,0 +1,63 @@
+mod common;
+
+use atcoder_bingo_backend::{
+    bingo::Cadence,
+    database::models::Problem,
+    feed::{group_by_date, render_atom, FeedEntry},
+};
+use chrono::{TimeZone, Utc};
+use common::problem;
+
+#[test]
+fn problems_are_grouped_by_date() {
+    let boards = group_by_date(vec![
+        problem("2022-05-02", 0),
+        problem("2022-05-02", 1),
+        problem("2022-05-01", 0),
+        // Boards of other cadences may start on the same date.
+        Problem {
+            cadence: Cadence::Weekly,
+            ..problem("2022-05-01", 0)
+        },
+    ]);
+
+    let sizes: Vec<(String, usize)> = boards
+        .iter()
+        .map(|(date, problems)| (date.to_string(), problems.len()))
+        .collect();
+    assert_eq!(
+        sizes,
+        [
+            ("2022-05-02".to_string(), 2),
+            ("2022-05-01".to_string(), 1),
+            ("2022-05-01".to_string(), 1)
+        ]
+    );
+}
+
+#[test]
+fn entries_list_problems_by_level() {
+    let problems = (0..18)
+        .map(|position| Problem {
+            title: "Tom & Jerry".to_string(),
+            difficulty: 100 * position,
+            ..problem("2022-05-01", position)
+        })
+        .collect();
+    let entries = [FeedEntry {
+        date: "2022-05-01".parse().unwrap(),
+        published: Utc.with_ymd_and_hms(2022, 4, 30, 15, 0, 0).unwrap(),
+        problems,
+    }];
+
+    let xml = render_atom("http://localhost/atcoder-bingo-api/feed.atom", &entries);
+    assert!(xml.contains("<updated>2022-04-30T15:00:00+00:00</updated>"));
+    assert!(xml.contains("<id>tag:atcoder-bingo,2022:daily/2022-05-01</id>"));
+    // The content is escaped HTML.
+    assert!(xml.contains("&lt;h3&gt;Level 1&lt;/h3&gt;"));
+    assert!(xml.contains("&lt;h3&gt;Level 2&lt;/h3&gt;"));
+    assert!(!xml.contains("Level 3"));
+    assert!(xml.contains(
+        "&lt;a href=&quot;https://atcoder.jp/contests/abc250/tasks/abc250_9&quot;&gt;Tom &amp;amp; Jerry&lt;/a&gt; (900)"
+    ));
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use atcoder_bingo_backend::{
+    database::models::{Problem, UserStatus},
+    image::{difficulty_color, render_svg},
+};
+use common::problem;
+
+fn accepted(problem_row_id: i32) -> UserStatus {
+    UserStatus {
+        user_id: "tourist".to_string(),
+        problem_row_id,
+        accepted: true,
+        accepted_time: None,
+        submission_id: None,
+    }
+}
+
+#[test]
+fn difficulty_colors_follow_atcoder_problems() {
+    assert_eq!(difficulty_color(-100), "#808080");
+    assert_eq!(difficulty_color(400), "#804000");
+    assert_eq!(difficulty_color(1999), "#0000ff");
+    assert_eq!(difficulty_color(4000), "#ff0000");
+}
+
+#[test]
+fn solved_cells_and_lines_are_drawn() {
+    let problems: Vec<Problem> = (0..18)
+        .map(|position| Problem {
+            title: "Takahashi & Aoki's Very Long Game".to_string(),
+            difficulty: 300 * position,
+            ..problem("2022-05-01", position)
+        })
+        .collect();
+    // The top row of the first level.
+    let user_status = vec![accepted(1), accepted(2), accepted(3), accepted(10)];
+
+    let svg = render_svg("AtCoder Bingo 2022-05-01: tourist", &problems, &user_status);
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert_eq!(svg.matches("fill=\"#c8e6c9\"").count(), 4);
+    assert_eq!(svg.matches("<line ").count(), 1);
+    assert!(svg.contains("Level 2"));
+    // Titles are escaped and truncated.
+    assert!(svg.contains(">Takahashi &amp; Aok…</text>"));
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn svg_is_rasterized_to_png() {
+    use atcoder_bingo_backend::image::render_png;
+
+    let svg = render_svg("AtCoder Bingo", &[problem("2022-05-01", 0)], &[]);
+    let png = render_png(&svg).unwrap();
+    assert!(png.starts_with(b"\x89PNG"));
+}
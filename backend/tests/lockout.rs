@@ -0,0 +1,157 @@
+mod common;
+
+use atcoder_bingo_backend::{
+    bingo::{count_lines, Band, SideBoard},
+    database::models::{Problem, Room, RoomMode},
+    lockout::{MatchEvent, MatchState, COUNTDOWN_SECS},
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use common::problem;
+use serde_json::json;
+
+fn time(hour: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2022, 5, 1, hour, 0, 0).unwrap()
+}
+
+fn board(side: &str, cells: &[usize]) -> SideBoard {
+    let mut filled = vec![false; 9];
+    for &cell in cells {
+        filled[cell] = true;
+    }
+    SideBoard {
+        side: side.to_string(),
+        lines: count_lines(&filled),
+        filled,
+    }
+}
+
+fn state(boards: Vec<SideBoard>) -> MatchState {
+    let room = Room {
+        id: "room".to_string(),
+        name: "Lockout".to_string(),
+        mode: RoomMode::Lockout,
+        bands: vec![Band {
+            lower: 0,
+            upper: 400,
+        }],
+        start_time: time(12),
+        end_time: time(13),
+    };
+    let problems = (0..9)
+        .map(|position| Problem {
+            room_id: Some("room".to_string()),
+            ..problem("2022-05-01", position)
+        })
+        .collect();
+    let members = vec!["alice".to_string(), "bob".to_string()];
+    MatchState::new(room, problems, members, boards)
+}
+
+#[test]
+fn countdown_starts_when_all_members_are_ready() {
+    let mut state = state(vec![board("alice", &[]), board("bob", &[])]);
+
+    assert!(state.join("carol").is_err());
+    assert_eq!(state.join("alice").unwrap().len(), 1);
+    assert!(state.join("alice").unwrap().is_empty());
+
+    let events = state.ready("alice", time(10)).unwrap();
+    assert_eq!(events.len(), 1);
+    let events = state.ready("bob", time(11)).unwrap();
+    let start_time = time(11) + Duration::seconds(COUNTDOWN_SECS);
+    assert_eq!(
+        serde_json::to_value(&events).unwrap(),
+        json!([
+            { "seq": 3, "type": "ready", "user_id": "bob" },
+            {
+                "seq": 4,
+                "type": "countdown",
+                "start_time": start_time,
+                "end_time": start_time + Duration::hours(1),
+            },
+        ])
+    );
+    assert_eq!(state.room().start_time, start_time);
+
+    // The match has started.
+    assert!(state.ready("alice", start_time).is_err());
+}
+
+#[test]
+fn match_ends_when_a_member_owns_the_majority() {
+    let mut state = state(vec![board("alice", &[]), board("bob", &[])]);
+
+    let events = state.update_boards(
+        vec![board("alice", &[0, 1, 2]), board("bob", &[4])],
+        time(12),
+    );
+    let claimed: Vec<(usize, String)> = events
+        .into_iter()
+        .filter_map(|event| match event.event {
+            MatchEvent::CellClaimed { position, user_id } => Some((position, user_id)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        claimed,
+        [
+            (0, "alice".to_string()),
+            (1, "alice".to_string()),
+            (2, "alice".to_string()),
+            (4, "bob".to_string()),
+        ]
+    );
+    assert!(!state.is_ended());
+
+    let events = state.update_boards(
+        vec![board("alice", &[0, 1, 2, 3, 5]), board("bob", &[4])],
+        time(12),
+    );
+    assert!(matches!(
+        &events.last().unwrap().event,
+        MatchEvent::MatchEnd { winner: Some(winner), .. } if winner == "alice"
+    ));
+    assert!(state.is_ended());
+    assert!(state
+        .update_boards(
+            vec![board("alice", &[]), board("bob", &[6, 7, 8])],
+            time(12)
+        )
+        .is_empty());
+}
+
+#[test]
+fn match_ends_in_a_draw_when_the_time_is_over() {
+    let mut state = state(vec![board("alice", &[0]), board("bob", &[8])]);
+
+    assert!(state.check_end(time(12)).is_empty());
+    let events = state.check_end(time(13));
+    assert!(matches!(
+        events[0].event,
+        MatchEvent::MatchEnd { winner: None, .. }
+    ));
+}
+
+#[test]
+fn missed_events_are_replayed_or_replaced_with_a_snapshot() {
+    let mut state = state(vec![board("alice", &[]), board("bob", &[])]);
+    state.join("alice").unwrap();
+    state.join("bob").unwrap();
+    state.update_boards(vec![board("alice", &[4]), board("bob", &[])], time(12));
+
+    let seqs: Vec<u64> = state
+        .events_after(1)
+        .unwrap()
+        .iter()
+        .map(|event| event.seq)
+        .collect();
+    assert_eq!(seqs, [2, 3]);
+    assert!(state.events_after(3).unwrap().is_empty());
+    // The client has seen events which this server does not know.
+    assert!(state.events_after(4).is_none());
+
+    let snapshot = state.snapshot();
+    assert_eq!(snapshot.seq, 3);
+    assert_eq!(snapshot.claims[4].as_deref(), Some("alice"));
+    assert!(snapshot.claims[0].is_none());
+}
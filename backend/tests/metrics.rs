@@ -0,0 +1,25 @@
+use atcoder_bingo_backend::metrics::metrics;
+
+#[test]
+fn render_in_prometheus_text_format() {
+    let metrics = metrics();
+    metrics
+        .user_status_writes
+        .with_label_values(&["insert"])
+        .inc_by(3);
+    metrics
+        .board_generation_seconds
+        .with_label_values(&["daily", "ok"])
+        .observe(1.5);
+    metrics.last_crawl_timestamp_seconds.set(1651363200.0);
+
+    let text = metrics.render();
+    assert!(text.contains("# TYPE bingo_user_status_writes_total counter"));
+    assert!(text.contains("bingo_user_status_writes_total{operation=\"insert\"} 3"));
+    assert!(text.contains(
+        "bingo_board_generation_duration_seconds_count{cadence=\"daily\",result=\"ok\"} 1"
+    ));
+    assert!(text.contains("bingo_crawler_last_crawl_timestamp_seconds 1651363200"));
+    // Metrics without labels are exported before they are observed.
+    assert!(text.contains("bingo_crawler_response_bytes_total 0"));
+}
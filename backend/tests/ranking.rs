@@ -0,0 +1,33 @@
+mod common;
+
+use atcoder_bingo_backend::ranking::{page_offset, RankingSort, RankingSpan};
+use common::date;
+
+#[test]
+fn last_30_days_include_today() {
+    let today = date("2022-05-30");
+    assert_eq!(RankingSpan::AllTime.since(today), None);
+    assert_eq!(
+        RankingSpan::Last30Days.since(today),
+        Some(date("2022-05-01"))
+    );
+}
+
+#[test]
+fn sorts_are_parsed_from_snake_case() {
+    let sort: RankingSort = serde_json::from_str("\"full_boards\"").unwrap();
+    assert_eq!(sort, RankingSort::FullBoards);
+    assert_eq!(sort.column(), "full_boards");
+    assert_eq!(RankingSort::default(), RankingSort::Score);
+
+    let span: RankingSpan = serde_json::from_str("\"last_30_days\"").unwrap();
+    assert_eq!(span, RankingSpan::Last30Days);
+    assert!(serde_json::from_str::<RankingSort>("\"score; DROP TABLE rankings\"").is_err());
+}
+
+#[test]
+fn page_offset_does_not_overflow() {
+    assert_eq!(page_offset(1, 50), Some(0));
+    assert_eq!(page_offset(3, 50), Some(100));
+    assert_eq!(page_offset(i64::MAX, 100), None);
+}
@@ -0,0 +1,275 @@
+mod common;
+
+use atcoder_bingo_backend::{
+    bingo::{
+        fill_boards, filled_by_side_of, room_sides, standings, validate_bands, Band, DEFAULT_BANDS,
+        MAX_BANDS,
+    },
+    config::Config,
+    crawler::submissions::Submission,
+    database::{
+        models::{Problem, Room, RoomMember, RoomMode, UserStatus},
+        DatabaseClient,
+    },
+    updater::update_user_status,
+};
+use common::{problem, utc};
+use rand::{distributions::Alphanumeric, Rng};
+use std::slice;
+
+fn random_id() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(12)
+        .map(char::from)
+        .collect()
+}
+
+fn member(user_id: &str, team: Option<&str>) -> RoomMember {
+    RoomMember {
+        user_id: user_id.to_string(),
+        team: team.map(str::to_string),
+    }
+}
+
+/// AC of `user_id` for the problem at `position` at `minute` past noon.
+fn accepted(user_id: &str, position: i32, minute: u32) -> UserStatus {
+    UserStatus {
+        user_id: user_id.to_string(),
+        problem_row_id: position + 1,
+        accepted: true,
+        accepted_time: Some(utc(&format!("2022-05-01T12:{minute:02}:00Z"))),
+        submission_id: Some(minute as i64),
+    }
+}
+
+fn board() -> Vec<Problem> {
+    (0..9)
+        .map(|position| problem("2022-05-01", position))
+        .collect()
+}
+
+#[test]
+fn the_first_side_owns_the_cell_in_claim_first_rooms() {
+    let members = [
+        member("alice", Some("red")),
+        member("bob", Some("red")),
+        member("carol", Some("blue")),
+    ];
+    let sides = room_sides(RoomMode::TeamClaimFirst, &members);
+    let user_status = [
+        accepted("carol", 0, 5),
+        accepted("alice", 0, 1),
+        accepted("bob", 1, 2),
+        accepted("carol", 1, 3),
+        accepted("carol", 2, 4),
+    ];
+
+    let boards = fill_boards(&board(), &sides, &user_status, true);
+    let filled: Vec<(&str, Vec<bool>)> = boards
+        .iter()
+        .map(|board| (board.side.as_str(), board.filled[..3].to_vec()))
+        .collect();
+    assert_eq!(
+        filled,
+        [
+            ("blue", vec![false, false, true]),
+            ("red", vec![true, true, false])
+        ]
+    );
+
+    // Every team fills the cells in shared rooms.
+    let boards = fill_boards(&board(), &sides, &user_status, false);
+    assert_eq!(boards[0].filled[..3], [true, true, true]);
+    assert_eq!(boards[0].lines, [1]);
+    assert_eq!(boards[1].filled[..3], [true, true, false]);
+}
+
+#[test]
+fn sides_with_the_same_score_share_the_rank() {
+    let members = [
+        member("alice", None),
+        member("bob", None),
+        member("carol", None),
+    ];
+    let sides = room_sides(RoomMode::Lockout, &members);
+    let user_status = [
+        accepted("alice", 0, 1),
+        accepted("alice", 1, 2),
+        accepted("alice", 2, 3),
+        accepted("bob", 3, 4),
+        accepted("bob", 5, 5),
+        accepted("carol", 4, 6),
+        accepted("carol", 8, 7),
+    ];
+
+    let ranks: Vec<(String, usize, usize, usize)> =
+        standings(&fill_boards(&board(), &sides, &user_status, true))
+            .into_iter()
+            .map(|standing| (standing.side, standing.rank, standing.cells, standing.lines))
+            .collect();
+    assert_eq!(
+        ranks,
+        [
+            ("alice".to_string(), 1, 3, 1),
+            ("bob".to_string(), 2, 2, 0),
+            ("carol".to_string(), 2, 2, 0),
+        ]
+    );
+}
+
+#[test]
+fn cells_claimed_by_others_make_no_line() {
+    let members = [member("alice", None), member("bob", None)];
+    // Bob solves the top row, but Alice claimed a cell of it first.
+    let user_status = [
+        accepted("alice", 1, 1),
+        accepted("bob", 0, 2),
+        accepted("bob", 1, 3),
+        accepted("bob", 2, 4),
+    ];
+
+    let filled = filled_by_side_of(RoomMode::Lockout, &members, &board(), &user_status, "bob");
+    assert_eq!(filled[..3], [true, false, true]);
+    // Without claims, the row is Bob's.
+    let filled = filled_by_side_of(
+        RoomMode::Individual,
+        &members,
+        &board(),
+        &user_status,
+        "bob",
+    );
+    assert_eq!(filled[..3], [true, true, true]);
+    assert!(
+        filled_by_side_of(RoomMode::Lockout, &members, &board(), &user_status, "dave")
+            .iter()
+            .all(|&cell| !cell)
+    );
+}
+
+#[test]
+fn bands_are_limited() {
+    assert!(validate_bands(&DEFAULT_BANDS).is_ok());
+    assert!(validate_bands(&[]).is_err());
+    assert!(validate_bands(&[DEFAULT_BANDS[0]; MAX_BANDS]).is_ok());
+    assert!(validate_bands(&[DEFAULT_BANDS[0]; MAX_BANDS + 1]).is_err());
+    let empty = Band {
+        lower: 400,
+        upper: 400,
+    };
+    assert!(validate_bands(&[empty]).is_err());
+}
+
+#[test]
+fn teams_must_be_in_the_room() {
+    let teams = ["red", "blue"];
+    assert!(RoomMode::TeamShared
+        .validate_team(Some("red"), &teams)
+        .is_ok());
+    // A typo does not make a new team.
+    assert!(RoomMode::TeamShared
+        .validate_team(Some("rde"), &teams)
+        .is_err());
+    assert!(RoomMode::TeamClaimFirst
+        .validate_team(None, &teams)
+        .is_err());
+
+    assert!(RoomMode::Individual.validate_team(None, &[]).is_ok());
+    assert!(RoomMode::Lockout.validate_team(Some("red"), &[]).is_err());
+}
+
+/// Run only when `POSTGRES_URL` points to a database for tests.
+#[tokio::test]
+async fn submissions_outside_the_window_are_ignored() {
+    if std::env::var("POSTGRES_URL").is_err() {
+        return;
+    }
+    let client = DatabaseClient::new().await;
+    client.migrate().await.unwrap();
+
+    let room = Room {
+        id: random_id(),
+        name: "Window".to_string(),
+        mode: RoomMode::Individual,
+        bands: vec![DEFAULT_BANDS[0]],
+        start_time: utc("2022-05-01T12:00:00Z"),
+        end_time: utc("2022-05-01T13:00:00Z"),
+    };
+    let member = RoomMember {
+        user_id: random_id(),
+        team: None,
+    };
+    // The problem is on no global board.
+    let problem = Problem {
+        problem_id: random_id(),
+        room_id: Some(room.id.clone()),
+        ..problem("2022-05-01", 0)
+    };
+    client
+        .insert_room(&room, slice::from_ref(&member), slice::from_ref(&problem))
+        .await
+        .unwrap();
+
+    let config = Config::default();
+    let submission = |time: &str| Submission {
+        id: rand::thread_rng().gen(),
+        submission_time: utc(time),
+        problem_id: problem.problem_id.clone(),
+        user_id: member.user_id.clone(),
+        is_accepted: true,
+    };
+    for time in ["2022-05-01T11:59:59Z", "2022-05-01T13:00:00Z"] {
+        let updated = update_user_status(&client, &config, &submission(time), false)
+            .await
+            .unwrap();
+        assert!(!updated, "{time}");
+    }
+    assert!(client
+        .select_user_status_by_room_id(&room.id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let updated = update_user_status(&client, &config, &submission("2022-05-01T12:30:00Z"), false)
+        .await
+        .unwrap();
+    assert!(updated);
+    let user_status = client
+        .select_user_status_by_room_id(&room.id)
+        .await
+        .unwrap();
+    assert_eq!(user_status.len(), 1);
+    assert!(user_status[0].accepted);
+}
+
+#[test]
+fn earlier_acs_are_accepted_before() {
+    let first = accepted("alice", 0, 1);
+    let second = accepted("bob", 0, 2);
+    assert!(first.accepted_before(&second));
+    assert!(!second.accepted_before(&first));
+
+    let rejected = UserStatus {
+        accepted: false,
+        accepted_time: None,
+        submission_id: None,
+        ..accepted("carol", 0, 0)
+    };
+    assert!(second.accepted_before(&rejected));
+    assert!(!rejected.accepted_before(&first));
+}
+
+#[test]
+fn ties_are_broken_by_the_submission_id() {
+    let first = UserStatus {
+        submission_id: Some(10),
+        ..accepted("alice", 0, 1)
+    };
+    let second = UserStatus {
+        submission_id: Some(11),
+        ..accepted("bob", 0, 1)
+    };
+    assert!(first.accepted_before(&second));
+    assert!(!second.accepted_before(&first));
+    assert!(!first.accepted_before(&first));
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use atcoder_bingo_backend::{
+    bingo::{BoardPeriod, Cadence},
+    config::Config,
+};
+use chrono::NaiveDate;
+use common::{date, utc};
+use cron::Schedule;
+use std::str::FromStr;
+
+#[test]
+fn boards_are_generated_at_ten_to_midnight_in_tokyo() {
+    let config = Config::default();
+
+    // 12:00 and 23:50 in JST.
+    assert_eq!(
+        config.next_choose_time(&utc("2022-05-01T03:00:00Z")),
+        Some(utc("2022-05-01T14:50:00Z"))
+    );
+    assert_eq!(
+        config.next_choose_time(&utc("2022-05-01T14:50:00Z")),
+        Some(utc("2022-05-02T14:50:00Z"))
+    );
+}
+
+#[test]
+fn schedule_follows_the_configured_expression() {
+    let config = Config {
+        timezone: chrono_tz::UTC,
+        choose_schedule: Schedule::from_str("0 0 */6 * * *").unwrap(),
+        ..Config::default()
+    };
+
+    assert_eq!(
+        config.next_choose_time(&utc("2022-05-01T13:00:00Z")),
+        Some(utc("2022-05-01T18:00:00Z"))
+    );
+}
+
+#[test]
+fn daily_periods_cover_each_day() {
+    let periods = BoardPeriod::covering(Cadence::Daily, date("2022-05-01"), date("2022-05-03"));
+    let starts: Vec<NaiveDate> = periods.iter().map(|period| period.start).collect();
+    assert_eq!(
+        starts,
+        vec![date("2022-05-01"), date("2022-05-02"), date("2022-05-03")]
+    );
+}
+
+#[test]
+fn next_week_is_covered_on_sunday() {
+    // Sunday and the next Monday.
+    let periods = BoardPeriod::covering(Cadence::Weekly, date("2022-05-08"), date("2022-05-09"));
+    let starts: Vec<NaiveDate> = periods.iter().map(|period| period.start).collect();
+    assert_eq!(starts, vec![date("2022-05-02"), date("2022-05-09")]);
+
+    // Monday and Tuesday.
+    let periods = BoardPeriod::covering(Cadence::Weekly, date("2022-05-09"), date("2022-05-10"));
+    assert_eq!(periods.len(), 1);
+}
+
+#[test]
+fn next_month_is_covered_on_the_last_day() {
+    let periods = BoardPeriod::covering(Cadence::Monthly, date("2022-12-31"), date("2023-01-01"));
+    let starts: Vec<NaiveDate> = periods.iter().map(|period| period.start).collect();
+    assert_eq!(starts, vec![date("2022-12-01"), date("2023-01-01")]);
+}
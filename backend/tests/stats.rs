@@ -0,0 +1,81 @@
+mod common;
+
+use atcoder_bingo_backend::{
+    badge::render_badge,
+    bingo::Cadence,
+    database::models::Problem,
+    stats::{line_masks, progress_by_board, BadgeStats, BoardProgress},
+};
+use common::{date, problem};
+
+/// Solved the top row of the first level.
+fn bingo(cadence: Cadence, chosen_date: &str) -> Vec<Problem> {
+    (0..3)
+        .map(|position| Problem {
+            cadence,
+            ..problem(chosen_date, position)
+        })
+        .collect()
+}
+
+fn daily(chosen_date: &str, lines: usize) -> BoardProgress {
+    BoardProgress {
+        cadence: Cadence::Daily,
+        chosen_date: date(chosen_date),
+        cells: 3 * lines,
+        lines,
+    }
+}
+
+#[test]
+fn progress_is_counted_per_board() {
+    let mut problems = bingo(Cadence::Daily, "2022-05-02");
+    problems.extend(bingo(Cadence::Weekly, "2022-05-02"));
+    problems.push(problem("2022-05-01", 4));
+
+    assert_eq!(
+        progress_by_board(&problems),
+        [
+            BoardProgress {
+                cells: 1,
+                ..daily("2022-05-01", 0)
+            },
+            daily("2022-05-02", 1),
+            BoardProgress {
+                cadence: Cadence::Weekly,
+                ..daily("2022-05-02", 1)
+            },
+        ]
+    );
+}
+
+#[test]
+fn badge_shows_today_streak_and_lifetime() {
+    let mut problems = bingo(Cadence::Daily, "2022-05-01");
+    problems.extend(bingo(Cadence::Daily, "2022-05-02"));
+    problems.extend(bingo(Cadence::Monthly, "2022-05-01"));
+
+    let stats = BadgeStats::new(&progress_by_board(&problems), date("2022-05-02"), 2);
+    assert_eq!(
+        stats,
+        BadgeStats {
+            today: 1,
+            streak: 2,
+            lifetime: 3,
+        }
+    );
+
+    let svg = render_badge("AtCoder Bingo", "today 1 | streak 2 | total 3", "#4c1");
+    assert!(svg.contains("aria-label=\"AtCoder Bingo: today 1 | streak 2 | total 3\""));
+}
+
+#[test]
+fn line_masks_cover_each_line() {
+    let masks = line_masks();
+    assert_eq!(masks.len(), 8);
+    // The top row and the main diagonal.
+    assert!(masks.contains(&0b000_000_111));
+    assert!(masks.contains(&0b100_010_001));
+    // Every cell is on some line, and all of them make the full board.
+    assert_eq!(masks.iter().fold(0, |acc, mask| acc | mask), 0b111_111_111);
+}
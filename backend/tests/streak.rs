@@ -0,0 +1,186 @@
+mod common;
+
+use atcoder_bingo_backend::{
+    bingo::{BoardPeriod, Cadence},
+    database::{
+        models::{NewAuditEntry, Problem, UserStatus},
+        DatabaseClient,
+    },
+    streak::{self, compute, from_solved, qualified_kinds, Streak, StreakKind},
+};
+use chrono::{Duration, NaiveDate};
+use common::{date, problem, utc};
+use rand::{distributions::Alphanumeric, Rng};
+
+fn board(cells: &[usize]) -> Vec<bool> {
+    let mut filled = vec![false; 45];
+    for &cell in cells {
+        filled[cell] = true;
+    }
+    filled
+}
+
+#[test]
+fn kinds_are_named_with_one_indexed_levels() {
+    for (kind, name) in [
+        (StreakKind::Solved, "solved"),
+        (StreakKind::Bingo, "bingo"),
+        (StreakKind::FullLevel(0), "full_level_1"),
+    ] {
+        assert_eq!(kind.to_string(), name);
+        assert_eq!(name.parse::<StreakKind>().unwrap(), kind);
+    }
+    assert!("full_level_0".parse::<StreakKind>().is_err());
+}
+
+#[test]
+fn board_qualifies_for_kinds() {
+    assert!(qualified_kinds(&board(&[])).is_empty());
+    assert_eq!(qualified_kinds(&board(&[4])), [StreakKind::Solved]);
+    assert_eq!(
+        qualified_kinds(&board(&[9, 10, 11, 12, 13, 14, 15, 16, 17])),
+        [
+            StreakKind::Solved,
+            StreakKind::Bingo,
+            StreakKind::FullLevel(1)
+        ]
+    );
+}
+
+#[test]
+fn streak_is_extended_by_consecutive_days() {
+    let mut streak = Streak::start(date("2022-05-01"));
+    assert!(streak.extend(date("2022-05-02")));
+    assert!(streak.extend(date("2022-05-02")));
+    assert_eq!((streak.current, streak.longest), (2, 2));
+
+    assert!(streak.extend(date("2022-05-04")));
+    assert_eq!((streak.current, streak.longest), (1, 2));
+    // An older day needs a rebuild.
+    assert!(!streak.extend(date("2022-05-03")));
+    assert_eq!(streak.last_date, date("2022-05-04"));
+
+    assert_eq!(streak.current_at(date("2022-05-05")), 1);
+    assert_eq!(streak.current_at(date("2022-05-06")), 0);
+}
+
+#[test]
+fn streaks_are_computed_from_the_history() {
+    let streaks = compute(&[
+        (date("2022-05-01"), board(&[0, 1, 2])),
+        (date("2022-05-02"), board(&[0])),
+        (date("2022-05-03"), board(&[0, 4, 8])),
+        (date("2022-05-04"), board(&[3, 4, 5])),
+    ]);
+
+    assert_eq!(
+        streaks[&StreakKind::Solved],
+        Streak {
+            current: 4,
+            longest: 4,
+            last_date: date("2022-05-04"),
+        }
+    );
+    assert_eq!(
+        streaks[&StreakKind::Bingo],
+        Streak {
+            current: 2,
+            longest: 2,
+            last_date: date("2022-05-04"),
+        }
+    );
+    assert!(!streaks.contains_key(&StreakKind::FullLevel(0)));
+}
+
+#[test]
+fn streaks_are_stored_only_from_the_daily_boards() {
+    let weekly = Problem {
+        cadence: Cadence::Weekly,
+        ..problem("2022-05-03", 0)
+    };
+    let solved = [problem("2022-05-01", 0), problem("2022-05-02", 4), weekly];
+    let streaks = from_solved(&solved);
+    assert_eq!(streaks.len(), 1);
+    assert_eq!(streaks[0].kind, "solved");
+    assert_eq!(streaks[0].current, 2);
+    assert_eq!(streaks[0].longest, 2);
+    assert_eq!(streaks[0].last_date, date("2022-05-02"));
+}
+
+/// Run only when `POSTGRES_URL` points to a database for tests.
+#[tokio::test]
+async fn streaks_are_rebuilt_when_solved_boards_change() {
+    if std::env::var("POSTGRES_URL").is_err() {
+        return;
+    }
+    let client = DatabaseClient::new().await;
+    client.migrate().await.unwrap();
+
+    let mut rng = rand::thread_rng();
+    let user_id: String = (&mut rng)
+        .sample_iter(&Alphanumeric)
+        .take(12)
+        .map(char::from)
+        .collect();
+    // Dates far in the past, on which no other boards are stored.
+    let first =
+        NaiveDate::from_ymd_opt(1900, 1, 1).unwrap() + Duration::days(rng.gen_range(0..36500));
+    let periods: Vec<BoardPeriod> = [first, first + Duration::days(1)]
+        .into_iter()
+        .map(|start| BoardPeriod {
+            cadence: Cadence::Daily,
+            start,
+            end: start,
+        })
+        .collect();
+    let mut cells = Vec::new();
+    for period in &periods {
+        let cell = Problem {
+            chosen_date: period.start,
+            period_end: period.end,
+            ..problem("2022-05-01", 0)
+        };
+        assert!(client.insert_problems(&[cell]).await.unwrap());
+        let cell = client.select_problems_by_period(period).await.unwrap()[0].clone();
+        let status = UserStatus {
+            user_id: user_id.clone(),
+            problem_row_id: cell.id,
+            accepted: true,
+            accepted_time: Some(utc("2022-05-01T12:00:00Z")),
+            submission_id: Some(rng.gen()),
+        };
+        assert!(client.insert_user_status(&status).await.unwrap());
+        cells.push(cell);
+    }
+    streak::rebuild(&client, &user_id).await.unwrap();
+    let streaks = client.select_user_streaks(&user_id).await.unwrap();
+    assert_eq!((streaks[0].current, streaks[0].longest), (2, 2));
+
+    let audit = NewAuditEntry {
+        actor: "test".to_string(),
+        action: "test".to_string(),
+        detail: serde_json::Value::Null,
+    };
+    let swapped = Problem {
+        problem_id: "abc250_1".to_string(),
+        ..cells[1].clone()
+    };
+    client.update_problem(&swapped, &audit).await.unwrap();
+    let streaks = client.select_user_streaks(&user_id).await.unwrap();
+    assert_eq!((streaks[0].current, streaks[0].longest), (1, 1));
+    assert_eq!(streaks[0].last_date, first);
+
+    client
+        .delete_problems_by_period(&periods[0], &audit)
+        .await
+        .unwrap();
+    assert!(client
+        .select_user_streaks(&user_id)
+        .await
+        .unwrap()
+        .is_empty());
+    client
+        .delete_problems_by_period(&periods[1], &audit)
+        .await
+        .unwrap();
+}
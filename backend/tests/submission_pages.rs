@@ -0,0 +1,109 @@
+use anyhow::Result;
+use atcoder_bingo_backend::crawler::submissions::{
+    parse_submissions, Submission, SubmissionPages, SubmissionSource,
+};
+use chrono::DateTime;
+use std::fs;
+
+/// Pages recorded from the API, which are named after the requested second.
+struct FixtureSource;
+
+impl SubmissionSource for FixtureSource {
+    async fn get_submissions_from(&self, from_second: i64) -> Result<Vec<Submission>> {
+        let path = format!(
+            "{}/tests/fixtures/submissions/from_{from_second}.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let body = fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("Unexpected request from {from_second}"));
+        parse_submissions(&body)
+    }
+}
+
+/// Full pages which never end.
+struct EndlessSource;
+
+impl SubmissionSource for EndlessSource {
+    async fn get_submissions_from(&self, from_second: i64) -> Result<Vec<Submission>> {
+        let submissions = (0..4)
+            .map(|i| Submission {
+                id: ((from_second - 1651600000) * 10 + i) as u32,
+                submission_time: DateTime::from_timestamp(from_second + i, 0).unwrap(),
+                problem_id: "abc250_a".to_string(),
+                user_id: "tourist".to_string(),
+                is_accepted: true,
+            })
+            .collect();
+        Ok(submissions)
+    }
+}
+
+async fn fetch_all<S: SubmissionSource>(pages: &mut SubmissionPages<S>) -> Vec<Vec<u32>> {
+    let mut ids = Vec::new();
+    while let Some(page) = pages.next_page().await.unwrap() {
+        ids.push(page.iter().map(|submission| submission.id).collect());
+    }
+    ids
+}
+
+#[test]
+fn parse_recorded_page() {
+    let body = fs::read_to_string(format!(
+        "{}/tests/fixtures/submissions/from_1651400000.json",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+    .unwrap();
+    let submissions = parse_submissions(&body).unwrap();
+
+    assert_eq!(submissions.len(), 4);
+    assert_eq!(submissions[1].id, 32001001);
+    assert_eq!(submissions[1].user_id, "snuke");
+    assert_eq!(submissions[1].problem_id, "abc250_b");
+    assert_eq!(submissions[1].submission_time.timestamp(), 1651400001);
+    assert!(!submissions[1].is_accepted);
+    assert!(submissions[2].is_accepted);
+}
+
+#[tokio::test]
+async fn submissions_on_page_boundaries_are_not_duplicated() {
+    let begin_time = DateTime::from_timestamp(1651400000, 0).unwrap();
+    let mut pages = SubmissionPages::new(FixtureSource, begin_time).page_size(4);
+
+    let ids = fetch_all(&mut pages).await;
+    assert_eq!(
+        ids,
+        vec![
+            vec![32001000, 32001001, 32001002, 32001003],
+            vec![32001004, 32001005],
+            vec![32001006],
+        ]
+    );
+    assert_eq!(pages.begin_time().timestamp(), 1651400005);
+}
+
+#[tokio::test]
+async fn full_page_within_one_second_does_not_stall() {
+    let begin_time = DateTime::from_timestamp(1651500000, 0).unwrap();
+    let mut pages = SubmissionPages::new(FixtureSource, begin_time).page_size(4);
+
+    let ids = fetch_all(&mut pages).await;
+    assert_eq!(
+        ids,
+        vec![vec![32100000, 32100001, 32100002, 32100003], vec![32100004]]
+    );
+}
+
+#[tokio::test]
+async fn number_of_pages_is_capped() {
+    let begin_time = DateTime::from_timestamp(1651600000, 0).unwrap();
+    let mut pages = SubmissionPages::new(EndlessSource, begin_time)
+        .page_size(4)
+        .max_pages(3);
+
+    let ids = fetch_all(&mut pages).await;
+    assert_eq!(ids.len(), 3);
+
+    // Each page starts from the newest second of the previous page.
+    assert_eq!(ids[1], vec![30, 31, 32, 33]);
+    assert!(pages.next_page().await.unwrap().is_none());
+}
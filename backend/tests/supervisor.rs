@@ -0,0 +1,75 @@
+use atcoder_bingo_backend::supervisor::{supervise, Backoff};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::time::{sleep, Instant};
+use tokio_util::sync::CancellationToken;
+
+#[test]
+fn backoff_doubles_up_to_max_and_resets() {
+    let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(5));
+
+    let delays: Vec<u64> = (0..5).map(|_| backoff.next_delay().as_secs()).collect();
+    assert_eq!(delays, vec![1, 2, 4, 5, 5]);
+
+    backoff.reset();
+    assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn failing_worker_is_restarted_with_backoff() {
+    let token = CancellationToken::new();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(60));
+
+    let supervisor = tokio::spawn(supervise("worker", token.clone(), backoff, {
+        let runs = runs.clone();
+        move |_| {
+            let runs = runs.clone();
+            async move {
+                runs.fetch_add(1, Ordering::SeqCst);
+                panic!("The worker failed.");
+            }
+        }
+    }));
+
+    // Restarted after 1, 2 and 4 secs.
+    sleep(Duration::from_millis(7500)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 4);
+
+    // Cancelled while waiting for the next restart.
+    token.cancel();
+    supervisor.await.unwrap();
+    assert_eq!(runs.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancellation_waits_for_the_worker() {
+    let token = CancellationToken::new();
+    let finished = Arc::new(AtomicUsize::new(0));
+
+    let supervisor = tokio::spawn(supervise("worker", token.clone(), Backoff::default(), {
+        let finished = finished.clone();
+        move |token| {
+            let finished = finished.clone();
+            async move {
+                token.cancelled().await;
+                // Finish the job in process.
+                sleep(Duration::from_secs(10)).await;
+                finished.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }));
+
+    sleep(Duration::from_secs(1)).await;
+    let cancelled_at = Instant::now();
+    token.cancel();
+    supervisor.await.unwrap();
+
+    assert!(cancelled_at.elapsed() >= Duration::from_secs(10));
+    assert_eq!(finished.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use atcoder_bingo_backend::{
+    bingo::{BoardPeriod, Cadence},
+    config::Config,
+};
+use common::{date, utc};
+
+#[test]
+fn board_date_switches_at_midnight_in_tokyo() {
+    let config = Config::default();
+
+    // 23:59:59 and 00:00:00 in JST.
+    assert_eq!(
+        config.board_date(&utc("2022-05-01T14:59:59Z")),
+        date("2022-05-01")
+    );
+    assert_eq!(
+        config.board_date(&utc("2022-05-01T15:00:00Z")),
+        date("2022-05-02")
+    );
+}
+
+#[test]
+fn submissions_before_nine_in_tokyo_belong_to_the_tokyo_date() {
+    let config = Config::default();
+
+    // 00:30 and 08:59 in JST are still the previous day in UTC.
+    assert_eq!(
+        config.board_date(&utc("2022-05-01T15:30:00Z")),
+        date("2022-05-02")
+    );
+    assert_eq!(
+        config.board_date(&utc("2022-05-01T23:59:00Z")),
+        date("2022-05-02")
+    );
+    assert_eq!(
+        config.board_date(&utc("2022-05-02T00:00:00Z")),
+        date("2022-05-02")
+    );
+}
+
+#[test]
+fn board_date_follows_the_configured_timezone() {
+    let config = Config {
+        timezone: chrono_tz::UTC,
+        ..Config::default()
+    };
+
+    assert_eq!(
+        config.board_date(&utc("2022-05-01T23:59:59Z")),
+        date("2022-05-01")
+    );
+    assert_eq!(
+        config.board_date(&utc("2022-05-02T00:00:00Z")),
+        date("2022-05-02")
+    );
+}
+
+#[test]
+fn weekly_board_switches_at_monday_midnight_in_tokyo() {
+    let config = Config::default();
+
+    // Sunday 23:59:59 and Monday 00:00:00 in JST.
+    let sunday = config.board_date(&utc("2022-05-08T14:59:59Z"));
+    let monday = config.board_date(&utc("2022-05-08T15:00:00Z"));
+
+    let period = BoardPeriod::containing(Cadence::Weekly, sunday);
+    assert_eq!(period.start, date("2022-05-02"));
+    assert_eq!(period.end, date("2022-05-08"));
+
+    let period = BoardPeriod::containing(Cadence::Weekly, monday);
+    assert_eq!(period.start, date("2022-05-09"));
+    assert_eq!(period.end, date("2022-05-15"));
+}
+
+#[test]
+fn monthly_board_switches_at_new_year_in_tokyo() {
+    let config = Config::default();
+
+    let new_year = config.board_date(&utc("2022-12-31T15:00:00Z"));
+    let period = BoardPeriod::containing(Cadence::Monthly, new_year);
+    assert_eq!(period.start, date("2023-01-01"));
+    assert_eq!(period.end, date("2023-01-31"));
+}
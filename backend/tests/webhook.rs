@@ -0,0 +1,225 @@
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use atcoder_bingo_backend::{
+    bingo::Cadence,
+    database::models::{Webhook, WebhookFormat},
+    events::BoardKey,
+    webhook::{
+        check_url, http_client, is_public_ip, render, retry_delay, send, sign, Notification,
+        MAX_ATTEMPTS, SIGNATURE_HEADER,
+    },
+};
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use std::net::{SocketAddr, TcpListener};
+use tokio::sync::mpsc;
+
+fn notification() -> Notification {
+    Notification::LineCompleted {
+        user_id: "tourist".to_string(),
+        board: BoardKey {
+            cadence: Cadence::Daily,
+            chosen_date: "2022-05-01".parse().unwrap(),
+            room_id: None,
+        },
+        level: 1,
+        lines: 2,
+    }
+}
+
+fn webhook(url: String, format: WebhookFormat) -> Webhook {
+    Webhook {
+        id: 1,
+        url,
+        format,
+        secret: "secret".to_string(),
+        room_id: None,
+        created_at: Utc::now(),
+    }
+}
+
+/// Start a receiver which responds with `status`, and pass the requests to the returned channel.
+fn start_receiver(status: u16) -> (String, mpsc::UnboundedReceiver<(Option<String>, String)>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let server = HttpServer::new(move || {
+        let sender = sender.clone();
+        App::new().route(
+            "/hook",
+            web::post().to(move |req: HttpRequest, body: String| {
+                let signature = req
+                    .headers()
+                    .get(SIGNATURE_HEADER)
+                    .map(|value| value.to_str().unwrap().to_string());
+                sender.send((signature, body)).unwrap();
+                async move {
+                    HttpResponse::build(actix_web::http::StatusCode::from_u16(status).unwrap())
+                        .finish()
+                }
+            }),
+        )
+    })
+    .workers(1)
+    .listen(listener)
+    .unwrap()
+    .run();
+    tokio::spawn(server);
+    (url, receiver)
+}
+
+/// Start a server which redirects every request to `location`, and return its URL.
+fn start_redirector(location: String) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let server = HttpServer::new(move || {
+        let location = location.clone();
+        App::new().default_service(web::to(move || {
+            let location = location.clone();
+            async move {
+                HttpResponse::Found()
+                    .insert_header(("Location", location))
+                    .finish()
+            }
+        }))
+    })
+    .workers(1)
+    .listen(listener)
+    .unwrap()
+    .run();
+    tokio::spawn(server);
+    url
+}
+
+fn socket_addr(url: &str) -> SocketAddr {
+    reqwest::Url::parse(url)
+        .unwrap()
+        .socket_addrs(|| None)
+        .unwrap()[0]
+}
+
+#[test]
+fn signature_is_hmac_sha256_of_the_body() {
+    // Test case 2 of RFC 4231.
+    assert_eq!(
+        sign("Jefe", "what do ya want for nothing?"),
+        "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+}
+
+#[test]
+fn payloads_are_rendered_for_each_format() {
+    let notification = notification();
+    let message =
+        "tourist completed a line in level 2 of the daily board from 2022-05-01 (2 lines).";
+
+    let body: Value = serde_json::from_str(&render(WebhookFormat::Json, &notification)).unwrap();
+    assert_eq!(body["type"], "line_completed");
+    let parsed: Notification = serde_json::from_value(body).unwrap();
+    assert_eq!(parsed, notification);
+
+    let body: Value = serde_json::from_str(&render(WebhookFormat::Discord, &notification)).unwrap();
+    assert_eq!(body, json!({ "content": message }));
+    let body: Value = serde_json::from_str(&render(WebhookFormat::Slack, &notification)).unwrap();
+    assert_eq!(body, json!({ "text": message }));
+}
+
+#[test]
+fn retries_back_off_and_give_up() {
+    assert_eq!(retry_delay(1), Some(Duration::seconds(30)));
+    assert_eq!(retry_delay(2), Some(Duration::minutes(1)));
+    assert_eq!(retry_delay(MAX_ATTEMPTS - 1), Some(Duration::hours(6)));
+    assert_eq!(retry_delay(MAX_ATTEMPTS), None);
+}
+
+#[actix_web::test]
+async fn signed_payload_is_posted_to_the_receiver() {
+    let (url, mut requests) = start_receiver(204);
+    let http = reqwest::Client::new();
+
+    send(
+        &http,
+        &webhook(url, WebhookFormat::Discord),
+        &notification(),
+    )
+    .await
+    .unwrap();
+
+    let (signature, body) = requests.recv().await.unwrap();
+    assert_eq!(signature, Some(sign("secret", &body)));
+    assert_eq!(body, render(WebhookFormat::Discord, &notification()));
+}
+
+#[actix_web::test]
+async fn error_response_fails_the_delivery() {
+    let (url, mut requests) = start_receiver(500);
+    let http = reqwest::Client::new();
+
+    assert!(
+        send(&http, &webhook(url, WebhookFormat::Json), &notification())
+            .await
+            .is_err()
+    );
+    assert!(requests.recv().await.is_some());
+}
+
+#[test]
+fn internal_addresses_are_not_public() {
+    for ip in [
+        "127.0.0.1",
+        "10.1.2.3",
+        "172.16.0.1",
+        "192.168.1.1",
+        "169.254.169.254",
+        "100.64.0.1",
+        "0.0.0.0",
+        "0.1.2.3",
+        "198.18.0.1",
+        "224.0.0.1",
+        "::1",
+        "fd00::1",
+        "fe80::1",
+        "ff02::1",
+        "::ffff:127.0.0.1",
+    ] {
+        assert!(!is_public_ip(ip.parse().unwrap()), "{ip}");
+    }
+    assert!(is_public_ip("8.8.8.8".parse().unwrap()));
+    assert!(is_public_ip("2001:4860:4860::8888".parse().unwrap()));
+}
+
+#[tokio::test]
+async fn urls_of_internal_hosts_are_rejected() {
+    assert!(check_url("http://127.0.0.1:8080/hook").await.is_err());
+    assert!(check_url("http://[::1]/hook").await.is_err());
+    assert!(check_url("http://169.254.169.254/latest").await.is_err());
+    assert!(check_url("ftp://8.8.8.8/hook").await.is_err());
+    assert!(check_url("https://8.8.8.8/hook").await.is_ok());
+}
+
+#[actix_web::test]
+async fn redirects_are_not_followed() {
+    let (target, mut requests) = start_receiver(204);
+    let url = start_redirector(target);
+    let http = http_client("127.0.0.1", socket_addr(&url)).unwrap();
+
+    assert!(
+        send(&http, &webhook(url, WebhookFormat::Json), &notification())
+            .await
+            .is_err()
+    );
+    assert!(requests.try_recv().is_err());
+}
+
+#[actix_web::test]
+async fn requests_are_sent_to_the_checked_address() {
+    let (url, mut requests) = start_receiver(204);
+    // The host is not resolved by DNS but pinned to the receiver.
+    let addr = socket_addr(&url);
+    let http = http_client("webhook.invalid", addr).unwrap();
+    let url = format!("http://webhook.invalid:{}/hook", addr.port());
+
+    send(&http, &webhook(url, WebhookFormat::Json), &notification())
+        .await
+        .unwrap();
+    assert!(requests.recv().await.is_some());
+}